@@ -0,0 +1,85 @@
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use sudoku_snark::parity::Parity;
+use sudoku_snark::poseidon::mk_poseidon_config;
+use sudoku_snark::{
+    box_size, check_proof, hash_puzzle, mk_proof, mk_salt, mk_sudoku, setup, Commitment, PuzSol,
+    Puzzle, Solution,
+};
+
+/// A solved grid from the standard pattern, with every other cell given
+fn example<const N: usize>() -> PuzSol<N> {
+    let b = box_size(N).unwrap();
+    let solution: [[u8; N]; N] =
+        std::array::from_fn(|i| std::array::from_fn(|j| ((i % b * b + i / b + j) % N + 1) as u8));
+    let puzzle = std::array::from_fn(|i| {
+        std::array::from_fn(|j| if (i + j) % 2 == 0 { solution[i][j] } else { 0 })
+    });
+    PuzSol {
+        puzzle: Puzzle(puzzle),
+        solution: Solution(solution),
+    }
+}
+
+/// Setup, hashing, synthesis, proving and verifying of one grid size,
+/// each under its own group so sizes line up in the report
+fn bench_size<const N: usize>(c: &mut Criterion) {
+    let id = format!("{}x{}", N, N);
+    let poseidon_config = mk_poseidon_config::<Fr>(N);
+    let example = example::<N>();
+    let parity = Parity::default();
+
+    let mut group = c.benchmark_group("setup");
+    group.sample_size(10);
+    group.bench_function(&id, |b| {
+        b.iter(|| setup::<N, E>(&poseidon_config, &[], Commitment::Flat))
+    });
+    group.finish();
+
+    c.benchmark_group("hash_puzzle").bench_function(&id, |b| {
+        b.iter(|| hash_puzzle(&poseidon_config, &example.puzzle, &parity, &[]))
+    });
+
+    let (pk, vk) = setup::<N, E>(&poseidon_config, &[], Commitment::Flat);
+    let sudoku = mk_sudoku::<N, E>(
+        &poseidon_config,
+        &example,
+        &parity,
+        &[],
+        Commitment::Flat,
+        mk_salt(),
+        mk_salt(),
+    );
+
+    let mut group = c.benchmark_group("synthesis");
+    group.sample_size(10);
+    group.bench_function(&id, |b| {
+        b.iter(|| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            sudoku.clone().generate_constraints(cs.clone()).unwrap();
+            cs
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("prove");
+    group.sample_size(10);
+    group.bench_function(&id, |b| b.iter(|| mk_proof(&pk, &sudoku)));
+    group.finish();
+
+    let proof = mk_proof(&pk, &sudoku);
+    let inputs = sudoku.public_inputs();
+    c.benchmark_group("verify")
+        .bench_function(&id, |b| b.iter(|| check_proof::<E>(&vk, &inputs, &proof)));
+}
+
+fn sudoku(c: &mut Criterion) {
+    bench_size::<4>(c);
+    bench_size::<9>(c);
+    bench_size::<16>(c);
+}
+
+criterion_group!(benches, sudoku);
+criterion_main!(benches);
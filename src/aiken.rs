@@ -0,0 +1,80 @@
+//! Aiken module of a verifying key, for an on chain validator to depend on.
+//! Points are bytestring constants of their compressed encoding, named as in `params.json`.
+use ark_ec::pairing::Pairing;
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalSerialize;
+
+use crate::ss_serde::hex_vec;
+
+/// Aiken bytestring literal of the compressed encoding
+fn literal<T: CanonicalSerialize>(x: &T) -> String {
+    let mut bytes = vec![];
+    x.serialize_compressed(&mut bytes).unwrap();
+    format!("#\"{}\"", hex_vec(&bytes))
+}
+
+/// Source of the module, `name` saying which keys it is for
+pub fn verifying_key_module<E: Pairing>(name: &str, vk: &VerifyingKey<E>) -> String {
+    let mut source = format!(
+        "//// Verifying key of sudoku-snark keys {}, generated by export-plutus. Do not edit.\n",
+        name
+    );
+    let mut constant = |name: &str, value: String| {
+        source += &format!("\npub const {}: ByteArray =\n  {}\n", name, value)
+    };
+    constant("alpha1", literal(&vk.alpha_g1));
+    constant("beta2", literal(&vk.beta_g2));
+    constant("gamma2", literal(&vk.gamma_g2));
+    constant("delta2", literal(&vk.delta_g2));
+    let abc = (1..=vk.gamma_abc_g1.len())
+        .map(|i| format!("abc1{}", i))
+        .collect::<Vec<_>>();
+    for (name, p) in abc.iter().zip(&vk.gamma_abc_g1) {
+        constant(name, literal(p));
+    }
+    source += &format!(
+        "\n/// The points for the constant term then each public input, in order\npub const abc: List<ByteArray> =\n  [{}]\n",
+        abc.join(", ")
+    );
+    source
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+    use ark_serialize::CanonicalDeserialize;
+
+    use crate::aiken::verifying_key_module;
+    use crate::poseidon::mk_poseidon_config;
+    use crate::ss_serde::unhex;
+    use crate::{setup, Commitment};
+
+    /// Bytes of the constant `name` in the module source
+    fn constant(source: &str, name: &str) -> Vec<u8> {
+        let line = format!("pub const {}: ByteArray =\n  #\"", name);
+        let start = source.find(&line).unwrap() + line.len();
+        let end = start + source[start..].find('"').unwrap();
+        unhex(&source[start..end])
+    }
+
+    #[test]
+    fn test_verifying_key_module() {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let (_, vk) = setup::<2, Bls12_381>(&poseidon_config, &[], Commitment::Flat);
+        let source = verifying_key_module("my-keys", &vk);
+
+        let g1 =
+            |name: &str| G1Affine::deserialize_compressed(&constant(&source, name)[..]).unwrap();
+        let g2 =
+            |name: &str| G2Affine::deserialize_compressed(&constant(&source, name)[..]).unwrap();
+        assert_eq!(g1("alpha1"), vk.alpha_g1);
+        assert_eq!(g2("beta2"), vk.beta_g2);
+        assert_eq!(g2("gamma2"), vk.gamma_g2);
+        assert_eq!(g2("delta2"), vk.delta_g2);
+        for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+            assert_eq!(g1(&format!("abc1{}", i + 1)), *p);
+        }
+        assert!(source
+            .contains("pub const abc: List<ByteArray> =\n  [abc11, abc12, abc13, abc14, abc15]\n"));
+    }
+}
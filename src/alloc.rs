@@ -7,6 +7,8 @@ use ark_r1cs_std::{
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
 
+use crate::parity::ParityVar;
+use crate::samurai::{in_grid, SamuraiVar, SIZE};
 use crate::{PuzzleVar, SolutionVar};
 
 impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for PuzzleVar<N, F> {
@@ -46,3 +48,44 @@ impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for SolutionVar<N,
         Ok(x)
     }
 }
+
+impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for ParityVar<N, F> {
+    fn new_variable<T: Borrow<[[u8; N]; N]>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into();
+        let row = [(); N].map(|_| UInt8::constant(0));
+        let mut x = ParityVar([(); N].map(|_| row.clone()));
+        let value = f().map_or([[0; N]; N], |f| *f.borrow());
+        for (i, row) in value.into_iter().enumerate() {
+            for (j, cell) in row.into_iter().enumerate() {
+                x.0[i][j] = UInt8::new_variable(cs.clone(), || Ok(cell), mode)?;
+            }
+        }
+        Ok(x)
+    }
+}
+
+impl<F: PrimeField> AllocVar<[[u8; SIZE]; SIZE], F> for SamuraiVar<F> {
+    fn new_variable<T: Borrow<[[u8; SIZE]; SIZE]>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into();
+        let row = [(); SIZE].map(|_| UInt8::constant(0));
+        let mut x = SamuraiVar([(); SIZE].map(|_| row.clone()));
+        let value = f().map_or([[0; SIZE]; SIZE], |f| *f.borrow());
+        for (i, row) in value.into_iter().enumerate() {
+            for (j, cell) in row.into_iter().enumerate() {
+                // Cells outside every sub-grid stay constant zero
+                if in_grid(i, j) {
+                    x.0[i][j] = UInt8::new_variable(cs.clone(), || Ok(cell), mode)?;
+                }
+            }
+        }
+        Ok(x)
+    }
+}
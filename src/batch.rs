@@ -0,0 +1,116 @@
+//! Batch verification of Groth16 proofs under one verifying key.
+//! Each proof's equation `e(A, B) = e(alpha, beta) e(IC, gamma) e(C, delta)` is scaled by a
+//! random factor and the lot checked in one multi-pairing, so a bad proof slips through
+//! only with negligible chance. When the batch fails, proofs are checked one by one.
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{UniformRand, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+
+use crate::verify_proof;
+
+/// Indices of the bad proofs in the batch, empty when all verify
+pub fn verify_batch<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    batch: &[(Vec<E::ScalarField>, Proof<E>)],
+) -> Vec<usize> {
+    if batch.is_empty() || holds_together(vk, batch) {
+        return vec![];
+    }
+    batch
+        .iter()
+        .enumerate()
+        .filter(|(_, (inputs, proof))| !verify_proof(vk, inputs, proof))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Whether the random combination of all the pairing equations holds
+fn holds_together<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    batch: &[(Vec<E::ScalarField>, Proof<E>)],
+) -> bool {
+    if batch
+        .iter()
+        .any(|(inputs, _)| inputs.len() + 1 != vk.gamma_abc_g1.len())
+    {
+        return false;
+    }
+    let r = (0..batch.len())
+        .map(|_| E::ScalarField::rand(&mut rand::rngs::OsRng))
+        .collect::<Vec<_>>();
+
+    let mut g1 = vec![];
+    let mut g2 = vec![];
+    for ((_, proof), r) in batch.iter().zip(&r) {
+        g1.push((proof.a * r).into_affine());
+        g2.push(proof.b);
+    }
+    // Sum of r_i * (1, x_i), against the input bases
+    let mut scalars = vec![E::ScalarField::zero(); vk.gamma_abc_g1.len()];
+    for ((inputs, _), r) in batch.iter().zip(&r) {
+        scalars[0] += r;
+        for (s, x) in scalars[1..].iter_mut().zip(inputs) {
+            *s += *r * x;
+        }
+    }
+    let r_sum: E::ScalarField = r.iter().sum();
+    let ic = E::G1::msm(&vk.gamma_abc_g1, &scalars).unwrap();
+    let c = E::G1::msm(
+        &batch.iter().map(|(_, proof)| proof.c).collect::<Vec<_>>(),
+        &r,
+    )
+    .unwrap();
+    g1.extend([
+        (vk.alpha_g1.into_group() * -r_sum).into_affine(),
+        (-ic).into_affine(),
+        (-c).into_affine(),
+    ]);
+    g2.extend([vk.beta_g2, vk.gamma_g2, vk.delta_g2]);
+    E::multi_pairing(g1, g2).is_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Bls12_381 as E, Fr};
+
+    use crate::batch::{holds_together, verify_batch};
+    use crate::parity::Parity;
+    use crate::poseidon::mk_poseidon_config;
+    use crate::{mk_proof, mk_salt, mk_sudoku, setup, Commitment, PuzSol, Puzzle, Solution};
+
+    #[test]
+    fn test_verify_batch() {
+        let poseidon_config = mk_poseidon_config::<Fr>(4);
+        let (pk, vk) = setup::<4, E>(&poseidon_config, &[], Commitment::Flat);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]]),
+            solution: Solution([[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]]),
+        };
+        let mut batch = (0..3)
+            .map(|_| {
+                let sudoku = mk_sudoku::<4, E>(
+                    &poseidon_config,
+                    &example,
+                    &Parity::default(),
+                    &[],
+                    Commitment::Flat,
+                    mk_salt(),
+                    mk_salt(),
+                );
+                (sudoku.public_inputs(), mk_proof(&pk, &sudoku))
+            })
+            .collect::<Vec<_>>();
+        assert!(holds_together(&vk, &batch));
+        assert_eq!(verify_batch(&vk, &batch), Vec::<usize>::new());
+
+        // A proof given with another's inputs is caught, and pointed out
+        batch[1].1 = batch[2].1.clone();
+        assert!(!holds_together(&vk, &batch));
+        assert_eq!(verify_batch(&vk, &batch), vec![1]);
+
+        // As are inputs of the wrong length
+        batch[2].0.pop();
+        assert_eq!(verify_batch(&vk, &batch), vec![1, 2]);
+    }
+}
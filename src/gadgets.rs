@@ -0,0 +1,49 @@
+//! Gadgets over lines of cells, for clues given outside the grid.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    fields::fp::FpVar,
+    prelude::{Boolean, CondSelectGadget, EqGadget, FieldVar},
+    uint8::UInt8,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::cmp::CmpGadget;
+
+/// The value of a cell as a field element
+pub fn to_fp<F: PrimeField>(x: &UInt8<F>) -> Result<FpVar<F>, SynthesisError> {
+    Boolean::le_bits_to_fp_var(&x.to_bits_le()?)
+}
+
+/// Number of cells taller than every cell before them, ie. the
+/// skyscrapers seen looking along the line from its start.
+pub fn count_visible<F: PrimeField>(line: &[&UInt8<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let mut max = UInt8::constant(0);
+    let mut count = FpVar::zero();
+    for &cell in line {
+        let visible = cell.is_gt(&max)?;
+        count += FpVar::from(visible.clone());
+        max = UInt8::conditionally_select(&visible, cell, &max)?;
+    }
+    Ok(count)
+}
+
+/// Sum of the cells strictly between the two crusts, the cells equal to `lo` or `hi`.
+/// Assumes the line holds exactly one of each.
+pub fn sandwich_sum<F: PrimeField>(
+    line: &[&UInt8<F>],
+    lo: u8,
+    hi: u8,
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut inside = Boolean::FALSE;
+    let mut sum = FpVar::zero();
+    for &cell in line {
+        let is_crust = cell
+            .is_eq(&UInt8::constant(lo))?
+            .or(&cell.is_eq(&UInt8::constant(hi))?)?;
+        let counts = inside.and(&is_crust.not())?;
+        sum += FpVar::conditionally_select(&counts, &to_fp(cell)?, &FpVar::zero())?;
+        inside = inside.xor(&is_crust)?;
+    }
+    Ok(sum)
+}
@@ -0,0 +1,230 @@
+//! Hints: prove one cell of a committed solution without revealing the rest.
+//! The public inputs are the puzzle hash and solution hash from the sudoku proof,
+//! then the row, column and value of the cell.
+use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::prelude::{AllocVar, Boolean, CondSelectGadget, EqGadget};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::gadgets::to_fp;
+use crate::parity::{Parity, ParityVar};
+use crate::rules::{Cell, ExtraRule};
+use crate::Sudoku;
+use crate::{commit_solution_var, Commitment, Grid, Puzzle, PuzzleVar, Solution, SolutionVar};
+
+#[derive(Clone, Debug)]
+pub struct Hint<const N: usize, F: PrimeField> {
+    pub poseidon_config: PoseidonConfig<F>,
+    pub hash: Option<F>,
+    pub solution_hash: Option<F>,
+    /// The cell revealed
+    pub cell: Option<Cell>,
+    /// Its value in the solution
+    pub value: Option<u8>,
+    pub puzzle: Option<Puzzle<N>>,
+    pub parity: Option<Parity<N>>,
+    /// Rules and commitment of the sudoku keys, so the puzzle hash is the same
+    pub rules: Vec<ExtraRule>,
+    pub commitment: Commitment,
+    pub salt: Option<F>,
+    pub solution: Option<Solution<N>>,
+    pub solution_salt: Option<F>,
+}
+
+impl<const N: usize, F: PrimeField> Hint<N, F> {
+    /// Public inputs in the order the circuit allocates them
+    pub fn public_inputs(&self) -> Vec<F> {
+        let (row, col) = self.cell.unwrap_or_default();
+        vec![
+            self.hash.unwrap_or_default(),
+            self.solution_hash.unwrap_or_default(),
+            F::from(row as u64),
+            F::from(col as u64),
+            F::from(self.value.unwrap_or_default()),
+        ]
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for Hint<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let puzzle_grid: Grid<N> = self.puzzle.unwrap_or(Puzzle([[0_u8; N]; N])).0;
+        let puzzle_var = PuzzleVar::<N, F>::new_witness(cs.clone(), || Ok(puzzle_grid)).unwrap();
+        let parity_grid: Grid<N> = self.parity.unwrap_or_default().0;
+        let parity_var = ParityVar::<N, F>::new_witness(cs.clone(), || Ok(parity_grid)).unwrap();
+        let solution_grid: Grid<N> = self.solution.unwrap_or(Solution([[0_u8; N]; N])).0;
+        let solution_var =
+            SolutionVar::<N, F>::new_witness(cs.clone(), || Ok(solution_grid)).unwrap();
+
+        // Solution agrees with problem
+        for (p_row, s_row) in puzzle_var.0.iter().zip(&solution_var.0) {
+            for (p, s) in p_row.iter().zip(s_row) {
+                (p.is_eq(s)?.or(&p.is_eq(&UInt8::constant(0))?)?).enforce_equal(&Boolean::TRUE)?;
+            }
+        }
+
+        // Hashes agree with the puzzle and solution
+        let params_g =
+            CRHParametersVar::<F>::new_witness(cs.clone(), || Ok(&self.poseidon_config)).unwrap();
+        let salt_var = FpVar::new_witness(cs.clone(), || Ok(self.salt.unwrap_or_default()))?;
+        let hash_gadget = self.commitment.commit_var(
+            &params_g,
+            &puzzle_var.0,
+            &parity_var.0,
+            &self.rules,
+            &salt_var,
+        )?;
+        let hash_var = FpVar::new_input(cs.clone(), || {
+            self.hash.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        hash_gadget.enforce_equal(&hash_var)?;
+
+        let solution_salt_var =
+            FpVar::new_witness(cs.clone(), || Ok(self.solution_salt.unwrap_or_default()))?;
+        let solution_hash_gadget =
+            commit_solution_var(&params_g, &solution_var.0, &solution_salt_var)?;
+        let solution_hash_var = FpVar::new_input(cs.clone(), || {
+            self.solution_hash.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        solution_hash_gadget.enforce_equal(&solution_hash_var)?;
+
+        // The cell holds the value. Exactly one row and one column are picked.
+        let (row, col) = self.cell.unwrap_or_default();
+        let row_var = FpVar::new_input(cs.clone(), || Ok(F::from(row as u64)))?;
+        let col_var = FpVar::new_input(cs.clone(), || Ok(F::from(col as u64)))?;
+        let value_var =
+            FpVar::new_input(cs.clone(), || Ok(F::from(self.value.unwrap_or_default())))?;
+        let is_row = (0..N)
+            .map(|i| row_var.is_eq(&FpVar::constant(F::from(i as u64))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let is_col = (0..N)
+            .map(|j| col_var.is_eq(&FpVar::constant(F::from(j as u64))))
+            .collect::<Result<Vec<_>, _>>()?;
+        Boolean::kary_or(&is_row)?.enforce_equal(&Boolean::TRUE)?;
+        Boolean::kary_or(&is_col)?.enforce_equal(&Boolean::TRUE)?;
+        let mut picked = FpVar::zero();
+        for (i, s_row) in solution_var.0.iter().enumerate() {
+            for (j, s) in s_row.iter().enumerate() {
+                let here = is_row[i].and(&is_col[j])?;
+                picked += FpVar::conditionally_select(&here, &to_fp(s)?, &FpVar::zero())?;
+            }
+        }
+        picked.enforce_equal(&value_var)?;
+
+        Ok(())
+    }
+}
+
+/// The circuit without a witness, as setup synthesizes it
+pub fn blank_hint<const N: usize, F: PrimeField>(
+    poseidon_config: &PoseidonConfig<F>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> Hint<N, F> {
+    Hint {
+        poseidon_config: poseidon_config.clone(),
+        hash: None,
+        solution_hash: None,
+        cell: None,
+        value: None,
+        puzzle: None,
+        parity: None,
+        rules: rules.to_vec(),
+        commitment,
+        salt: None,
+        solution: None,
+        solution_salt: None,
+    }
+}
+
+pub fn setup<const N: usize, E>(
+    poseidon_config: &PoseidonConfig<E::ScalarField>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> (ProvingKey<E>, VerifyingKey<E>)
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    // Secrets from the OS, as for the main circuit
+    let mut rng = rand::rngs::OsRng;
+
+    Groth16::<E>::setup(
+        blank_hint::<N, E::ScalarField>(poseidon_config, rules, commitment),
+        &mut rng,
+    )
+    .unwrap()
+}
+
+/// A hint at `cell` of the sudoku's solution
+pub fn mk_hint<const N: usize, F: PrimeField>(sudoku: &Sudoku<N, F>, cell: Cell) -> Hint<N, F> {
+    let solution = sudoku.solution.unwrap();
+    Hint {
+        poseidon_config: sudoku.poseidon_config.clone(),
+        hash: sudoku.hash,
+        solution_hash: sudoku.solution_hash,
+        cell: Some(cell),
+        value: Some(solution.0[cell.0][cell.1]),
+        puzzle: sudoku.puzzle,
+        parity: sudoku.parity,
+        rules: sudoku.rules.clone(),
+        commitment: sudoku.commitment,
+        salt: sudoku.salt,
+        solution: Some(solution),
+        solution_salt: sudoku.solution_salt,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Bls12_381 as E, Fr};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use crate::hint::{mk_hint, Hint};
+    use crate::parity::Parity;
+    use crate::poseidon::mk_poseidon_config;
+    use crate::{mk_salt, mk_sudoku, Commitment, PuzSol, Puzzle, Solution};
+
+    fn is_satisfied(hint: Hint<4, Fr>) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        hint.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_hint() {
+        let poseidon_config = mk_poseidon_config::<Fr>(4);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]]),
+            solution: Solution([[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]]),
+        };
+        let sudoku = mk_sudoku::<4, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            mk_salt(),
+            mk_salt(),
+        );
+        let hint = mk_hint(&sudoku, (2, 1));
+        assert_eq!(hint.value, Some(1));
+        assert!(is_satisfied(hint.clone()));
+
+        let mut wrong = hint.clone();
+        wrong.value = Some(2);
+        assert!(!is_satisfied(wrong));
+        let mut wrong = hint.clone();
+        wrong.cell = Some((4, 1));
+        assert!(!is_satisfied(wrong));
+        let mut wrong = hint;
+        wrong.solution_salt = Some(Fr::from(0));
+        assert!(!is_satisfied(wrong));
+    }
+}
@@ -1,59 +1,77 @@
 use std::convert::TryInto;
 use std::fs;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter};
 
 use ark_crypto_primitives::crh::poseidon::constraints::{CRHGadget, CRHParametersVar};
 use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
 use ark_crypto_primitives::sponge::Absorb;
-use ark_ec::bls12::Bls12;
 use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use ark_groth16::{prepare_verifying_key, Groth16, Proof, ProvingKey, VerifyingKey};
 use ark_r1cs_std::fields::fp::{AllocatedFp, FpVar};
-use ark_r1cs_std::{R1CSVar, ToConstraintFieldGadget};
-use ark_relations::lc;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::SynthesisError;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
-use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
 
-use ark_crypto_primitives::crh::poseidon::{TwoToOneCRH, CRH};
+use ark_crypto_primitives::crh::poseidon::CRH;
 use ark_r1cs_std::{
     prelude::{AllocVar, Boolean, EqGadget},
     uint8::UInt8,
 };
-use ark_serialize::{CanonicalSerialize, Compress, SerializationError};
 use ark_std::{
     rand::{RngCore, SeedableRng},
     test_rng,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+pub mod aiken;
 mod alloc;
-mod cmp;
-mod poseidon;
-mod ss_serde;
+pub mod batch;
+pub mod cmp;
+pub mod gadgets;
+pub mod hint;
+pub mod merkle;
+pub mod pack;
+pub mod parity;
+pub mod plutus;
+pub mod poseidon;
+pub mod progress;
+pub mod rules;
+pub mod samurai;
+pub mod solidity;
+pub mod solver;
+pub mod ss_serde;
 
 use crate::cmp::CmpGadget;
+use crate::merkle::{merkle_root, merkle_root_var};
+use crate::pack::{cell_bits, pack, pack_var};
+use crate::parity::{enforce_parity, Parity, ParityVar, PARITY_BITS};
+use crate::rules::{ExtraRule, Rule};
 
-type Grid<const N: usize> = [[u8; N]; N];
+pub type Grid<const N: usize> = [[u8; N]; N];
 
-type GridVar<const N: usize, F> = [[UInt8<F>; N]; N];
+pub type GridVar<const N: usize, F> = [[UInt8<F>; N]; N];
 
 #[derive(Clone, Copy, Debug)]
 pub struct Puzzle<const N: usize>(pub Grid<N>);
 
 pub fn read_grid<const N: usize>(fp: &str) -> Grid<N> {
+    // Parity marks are blanks as far as the givens are concerned
+    read_cells(fp, |x| match x {
+        "o" | "e" => 0,
+        x => x.parse::<u8>().unwrap(),
+    })
+}
+
+pub(crate) fn read_cells<const N: usize>(fp: &str, cell: impl Fn(&str) -> u8) -> Grid<N> {
     fs::read_to_string(fp)
         .unwrap()
         .split("\n")
         .filter(|row| row.len() > 2)
-        .into_iter()
         .map(|row| {
             row.split_whitespace()
-                .into_iter()
-                .map(|x| x.parse::<u8>().unwrap())
+                .map(&cell)
                 .collect::<Vec<u8>>()
                 .try_into()
                 .unwrap_or_else(|v| {
@@ -95,6 +113,57 @@ pub struct Sudoku<const N: usize, F: PrimeField> {
     pub hash: Option<F>,
     pub puzzle: Option<Puzzle<N>>,
     pub solution: Option<Solution<N>>,
+    pub parity: Option<Parity<N>>,
+    /// Variant rules enforced on top of the base rules
+    pub rules: Vec<ExtraRule>,
+    /// How the puzzle hash commits to the puzzle
+    pub commitment: Commitment,
+    /// Blinding for the puzzle commitment, zero if unsalted
+    pub salt: Option<F>,
+    /// Identity of the solver the proof is bound to, packed by `pack_solver`
+    pub solver: Option<[F; SOLVER_CHUNKS]>,
+    /// Commitment to the solution, for revealing it later
+    pub solution_hash: Option<F>,
+    /// Blinding for the solution commitment, zero if unsalted
+    pub solution_salt: Option<F>,
+}
+
+/// Number of field elements the solver identity is packed into
+pub const SOLVER_CHUNKS: usize = 2;
+
+/// Bytes of solver identity per field element
+const SOLVER_CHUNK_BYTES: usize = 31;
+
+/// Longest solver identity, one byte of the chunks going to its length
+pub const SOLVER_BYTES: usize = SOLVER_CHUNKS * SOLVER_CHUNK_BYTES - 1;
+
+/// Pack the solver identity, eg. a public key hash or address, into field elements.
+/// The identity is prefixed with its length and zero padded to the full chunks,
+/// so distinct identities never pack the same.
+/// Each 31 byte chunk is then read as a big endian integer.
+pub fn pack_solver<F: PrimeField>(solver: &[u8]) -> [F; SOLVER_CHUNKS] {
+    assert!(
+        solver.len() <= SOLVER_BYTES,
+        "solver is longer than {} bytes",
+        SOLVER_BYTES
+    );
+    let mut bytes = [0_u8; SOLVER_CHUNKS * SOLVER_CHUNK_BYTES];
+    bytes[0] = solver.len() as u8;
+    bytes[1..=solver.len()].copy_from_slice(solver);
+    let mut chunks = bytes.chunks(SOLVER_CHUNK_BYTES);
+    [(); SOLVER_CHUNKS].map(|_| F::from_be_bytes_mod_order(chunks.next().unwrap()))
+}
+
+impl<const N: usize, F: PrimeField> Sudoku<N, F> {
+    /// Public inputs in the order the circuit allocates them
+    pub fn public_inputs(&self) -> Vec<F> {
+        let mut inputs = vec![
+            self.hash.unwrap_or_default(),
+            self.solution_hash.unwrap_or_default(),
+        ];
+        inputs.extend(self.solver.unwrap_or_default());
+        inputs
+    }
 }
 
 pub struct PuzSol<const N: usize> {
@@ -110,63 +179,151 @@ impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for Sudoku
         let solution_grid: Grid<N> = self.solution.unwrap_or(Solution([[0_u8; N]; N])).0;
         let solution_var: SolutionVar<N, F> =
             SolutionVar::<N, F>::new_witness(cs.clone(), || Ok(solution_grid)).unwrap();
-
-        // Solution agrees with problem
-        for (p_row, s_row) in puzzle_var.0.iter().zip(&solution_var.0) {
-            for (p, s) in p_row.iter().zip(s_row) {
-                s.is_leq(&UInt8::constant(N as u8))?
-                    .and(&s.is_geq(&UInt8::constant(1))?)?
-                    .enforce_equal(&Boolean::TRUE)?;
-                (p.is_eq(s)?.or(&p.is_eq(&UInt8::constant(0))?)?).enforce_equal(&Boolean::TRUE)?;
-            }
+        let parity_grid: Grid<N> = self.parity.unwrap_or_default().0;
+        let parity_var: ParityVar<N, F> =
+            ParityVar::<N, F>::new_witness(cs.clone(), || Ok(parity_grid)).unwrap();
+
+        enforce_base_rules(&puzzle_var.0, &solution_var.0)?;
+        enforce_parity(&parity_var.0, &solution_var.0)?;
+        // Solution satisfies any variant rules
+        for rule in &self.rules {
+            rule.generate_constraints(&solution_var)?;
         }
-        // Solution rows are distinct
-        for row in &solution_var.0 {
-            for (j, cell) in row.iter().enumerate() {
-                for prior_cell in &row[0..j] {
-                    cell.is_neq(&prior_cell)?.enforce_equal(&Boolean::TRUE)?;
-                }
-            }
-        }
-        // TODO: Add additional constraints
 
         // Hash puzzle agrees with hash
-        let x = puzzle_var
-            .0
-            .into_iter()
-            .flatten()
-            .collect::<Vec<UInt8<F>>>()
-            .to_constraint_field()?;
+        let salt_var = FpVar::new_witness(cs.clone(), || Ok(self.salt.unwrap_or_default()))?;
         let params_g =
             CRHParametersVar::<F>::new_witness(cs.clone(), || Ok(&self.poseidon_config)).unwrap();
-        let hash_gadget = CRHGadget::<F>::evaluate(&params_g, &x).unwrap();
+        let hash_gadget = self.commitment.commit_var(
+            &params_g,
+            &puzzle_var.0,
+            &parity_var.0,
+            &self.rules,
+            &salt_var,
+        )?;
 
         let hash_var =
             cs.new_input_variable(|| self.hash.ok_or(SynthesisError::AssignmentMissing))?;
 
         let hash_fp = FpVar::Var(AllocatedFp::new(self.hash, hash_var, cs.clone()));
-        let _u = hash_gadget.is_eq(&hash_fp);
+        hash_gadget.enforce_equal(&hash_fp)?;
+
+        // Hash solution agrees with solution hash
+        let solution_salt_var =
+            FpVar::new_witness(cs.clone(), || Ok(self.solution_salt.unwrap_or_default()))?;
+        let solution_hash_gadget =
+            commit_solution_var(&params_g, &solution_var.0, &solution_salt_var)?;
+        let solution_hash_var = FpVar::new_input(cs.clone(), || {
+            self.solution_hash.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        solution_hash_gadget.enforce_equal(&solution_hash_var)?;
+
+        // Bind the proof to the solver. Squaring puts each input in a constraint.
+        let solver = self.solver.unwrap_or_default();
+        for chunk in solver {
+            let chunk_var = FpVar::new_input(cs.clone(), || Ok(chunk))?;
+            let _ = chunk_var.square()?;
+        }
 
         Ok(())
     }
 }
 
+/// Enforce that the solution is in range and agrees with the puzzle,
+/// and that its rows, columns and (for square sizes) boxes are distinct.
+pub fn enforce_base_rules<const N: usize, F: PrimeField>(
+    puzzle: &GridVar<N, F>,
+    solution: &GridVar<N, F>,
+) -> Result<(), SynthesisError> {
+    // Solution agrees with problem
+    for (p_row, s_row) in puzzle.iter().zip(solution) {
+        for (p, s) in p_row.iter().zip(s_row) {
+            s.is_leq(&UInt8::constant(N as u8))?
+                .and(&s.is_geq(&UInt8::constant(1))?)?
+                .enforce_equal(&Boolean::TRUE)?;
+            (p.is_eq(s)?.or(&p.is_eq(&UInt8::constant(0))?)?).enforce_equal(&Boolean::TRUE)?;
+        }
+    }
+    // Solution rows, columns and boxes are distinct
+    for house in houses(solution) {
+        enforce_distinct(&house)?;
+    }
+    Ok(())
+}
+
+/// Rows, then columns, then (for square sizes) boxes of a grid
+pub fn houses<const N: usize, T>(grid: &[[T; N]; N]) -> Vec<Vec<&T>> {
+    let mut houses = vec![];
+    for row in grid {
+        houses.push(row.iter().collect());
+    }
+    for j in 0..N {
+        houses.push(grid.iter().map(|row| &row[j]).collect());
+    }
+    if let Some(b) = box_size(N) {
+        for i in (0..N).step_by(b) {
+            for j in (0..N).step_by(b) {
+                houses.push(
+                    grid[i..i + b]
+                        .iter()
+                        .flat_map(|row| &row[j..j + b])
+                        .collect(),
+                );
+            }
+        }
+    }
+    houses
+}
+
+fn enforce_distinct<F: PrimeField>(cells: &[&UInt8<F>]) -> Result<(), SynthesisError> {
+    for (j, cell) in cells.iter().enumerate() {
+        for prior_cell in &cells[0..j] {
+            cell.is_neq(prior_cell)?.enforce_equal(&Boolean::TRUE)?;
+        }
+    }
+    Ok(())
+}
+
+/// Side of the boxes of an `n` by `n` grid, if `n` is a square
+pub fn box_size(n: usize) -> Option<usize> {
+    (2..n).find(|b| b * b == n)
+}
+
+/// The circuit without a witness, as setup synthesizes it
+pub fn blank_sudoku<const N: usize, F: PrimeField>(
+    poseidon_config: &PoseidonConfig<F>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> Sudoku<N, F> {
+    Sudoku {
+        poseidon_config: poseidon_config.clone(),
+        hash: None,
+        puzzle: None,
+        solution: None,
+        parity: None,
+        rules: rules.to_vec(),
+        commitment,
+        salt: None,
+        solver: None,
+        solution_hash: None,
+        solution_salt: None,
+    }
+}
+
 pub fn setup<const N: usize, E>(
     poseidon_config: &PoseidonConfig<E::ScalarField>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
 ) -> (ProvingKey<E>, VerifyingKey<E>)
 where
     E: Pairing,
     E::ScalarField: Absorb,
 {
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64() + 1);
+    // The setup secrets are the toxic waste, so must come from the OS and not a seed
+    let mut rng = rand::rngs::OsRng;
 
     Groth16::<E>::setup(
-        Sudoku::<N, E::ScalarField> {
-            poseidon_config: poseidon_config.clone(),
-            hash: None,
-            puzzle: None,
-            solution: None,
-        },
+        blank_sudoku::<N, E::ScalarField>(poseidon_config, rules, commitment),
         &mut rng,
     )
     .unwrap()
@@ -185,6 +342,13 @@ where
             hash: None,
             puzzle: None,
             solution: None,
+            parity: None,
+            rules: vec![],
+            commitment: Commitment::Flat,
+            salt: None,
+            solver: None,
+            solution_hash: None,
+            solution_salt: None,
         },
         &mut rng,
     )
@@ -192,81 +356,587 @@ where
 
     let pvk = prepare_verifying_key::<E>(&vk);
 
-    let puzzle = Puzzle([[1, 0], [0, 2]]);
-    let hash = hash_puzzle(&poseidon_config, &puzzle);
+    let puzzle = Puzzle([[1, 0], [0, 1]]);
+    let hash = hash_puzzle(&poseidon_config, &puzzle, &Parity::default(), &[]);
 
-    let solution = Solution([[1, 2], [1, 2]]);
+    let solution = Solution([[1, 2], [2, 1]]);
+    let solution_hash = commit_solution(&poseidon_config, &solution, E::ScalarField::zero());
     let sudoku = Sudoku {
         poseidon_config: poseidon_config.clone(),
-        hash: Some(hash.clone()),
-        puzzle: Some(Puzzle(puzzle.0.clone())),
+        hash: Some(hash),
+        puzzle: Some(Puzzle(puzzle.0)),
         solution: Some(solution),
+        parity: None,
+        rules: vec![],
+        commitment: Commitment::Flat,
+        salt: None,
+        solver: None,
+        solution_hash: Some(solution_hash),
+        solution_salt: None,
     };
+    let inputs = sudoku.public_inputs();
     let proof = Groth16::<E>::prove(&pk, sudoku, &mut rng).unwrap();
     // let x = puzzle.0.into_iter().flatten().map(|x| x.into()).collect();
     // hash_vec(poseidon_config.clone(), x);
 
-    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[hash], &proof).unwrap());
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
     // assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &puzzle, &proof).unwrap());
 }
 
-pub fn mk_proof<const N: usize, E>(
-    pk: &ProvingKey<E>,
-    sudoku: &Sudoku<N, E::ScalarField>,
-) -> Proof<E>
+pub fn mk_proof<E, C>(pk: &ProvingKey<E>, circuit: &C) -> Proof<E>
 where
     E: Pairing,
-    E::ScalarField: PrimeField + Absorb,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone,
 {
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
-    Groth16::<E>::prove(pk, sudoku.clone(), &mut rng).unwrap()
+    // Fresh blinding, so a proof cannot be checked against guessed solutions
+    Groth16::<E>::prove(pk, circuit.clone(), &mut rand::rngs::OsRng).unwrap()
 }
 
-pub fn check_proof<E>(vk: &VerifyingKey<E>, hash: &E::ScalarField, proof: &Proof<E>)
+/// A fresh proof of the same statement that cannot be linked to the original
+pub fn rerandomize<E: Pairing>(vk: &VerifyingKey<E>, proof: &Proof<E>) -> Proof<E> {
+    Groth16::<E>::rerandomize_proof(vk, proof, &mut rand::rngs::OsRng)
+}
+
+pub fn check_proof<E>(vk: &VerifyingKey<E>, inputs: &[E::ScalarField], proof: &Proof<E>)
 where
     E: Pairing,
     E::ScalarField: Absorb,
 {
-    let pvk = prepare_verifying_key::<E>(&vk);
-    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[hash.clone()], &proof).unwrap());
+    assert!(verify_proof(vk, inputs, proof));
+}
+
+pub fn verify_proof<E>(vk: &VerifyingKey<E>, inputs: &[E::ScalarField], proof: &Proof<E>) -> bool
+where
+    E: Pairing,
+{
+    let pvk = prepare_verifying_key::<E>(vk);
+    // Inputs of the wrong length are an error, rather than false
+    Groth16::<E>::verify_with_processed_vk(&pvk, inputs, proof).unwrap_or(false)
 }
 
 pub fn mk_sudoku<const N: usize, E>(
     poseidon_config: &PoseidonConfig<E::ScalarField>,
     example: &PuzSol<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+    salt: E::ScalarField,
+    solution_salt: E::ScalarField,
 ) -> Sudoku<N, E::ScalarField>
 where
     E: Pairing,
     E::ScalarField: Absorb,
 {
-    let hash = hash_puzzle(poseidon_config, &example.puzzle);
+    let hash = commitment.commit(poseidon_config, &example.puzzle, parity, rules, salt);
+    let solution_hash = commit_solution(poseidon_config, &example.solution, solution_salt);
     Sudoku {
         poseidon_config: poseidon_config.clone(),
-        hash: Some(hash.clone()),
-        puzzle: Some(example.puzzle.clone()),
-        solution: Some(example.solution.clone()),
+        hash: Some(hash),
+        puzzle: Some(example.puzzle),
+        solution: Some(example.solution),
+        parity: Some(*parity),
+        rules: rules.to_vec(),
+        commitment,
+        salt: Some(salt),
+        solver: None,
+        solution_hash: Some(solution_hash),
+        solution_salt: Some(solution_salt),
     }
 }
 
 pub fn example_2() -> PuzSol<2> {
     let fp = "tmp.txt";
-    let mut puzzle = Puzzle([[1, 0], [0, 2]]);
-    write_grid(&fp, puzzle.0).unwrap();
-    puzzle = Puzzle(read_grid::<2>(&fp));
-    let solution = Solution([[1, 2], [1, 2]]);
-    return PuzSol { puzzle, solution };
+    let mut puzzle = Puzzle([[1, 0], [0, 1]]);
+    write_grid(fp, puzzle.0).unwrap();
+    puzzle = Puzzle(read_grid::<2>(fp));
+    let solution = Solution([[1, 2], [2, 1]]);
+    PuzSol { puzzle, solution }
 }
 
+/// Unsalted commitment to a puzzle, ie. with a salt of zero
 pub fn hash_puzzle<const N: usize, F: PrimeField + Absorb>(
     poseidon_config: &PoseidonConfig<F>,
     puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
 ) -> F {
-    let puzzle_flat = puzzle
-        .0
-        .clone()
-        .into_iter()
-        .flatten()
-        .map(|x| x.into())
-        .collect::<Vec<F>>();
-    CRH::<F>::evaluate(poseidon_config, puzzle_flat).unwrap()
+    commit_puzzle(poseidon_config, puzzle, parity, rules, F::zero())
+}
+
+/// A random salt, for a commitment which hides the puzzle
+pub fn mk_salt<F: PrimeField>() -> F {
+    F::rand(&mut rand::rngs::OsRng)
+}
+
+/// Commitment to a puzzle, hiding it when the salt is random
+pub fn commit_puzzle<const N: usize, F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
+    salt: F,
+) -> F {
+    let cells = puzzle.0.into_iter().flatten().map(|x| (x, cell_bits(N)));
+    let marks = parity.0.into_iter().flatten().map(|x| (x, PARITY_BITS));
+    let rule_bytes = rules
+        .iter()
+        .flat_map(|rule| rule.to_bytes())
+        .map(|x| (x, 8));
+    let mut x = pack::<F>(&cells.chain(marks).chain(rule_bytes).collect::<Vec<_>>());
+    x.push(salt);
+    CRH::<F>::evaluate(poseidon_config, x).unwrap()
+}
+
+/// How a puzzle is committed to. Part of the circuit, so fixed by the keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    /// Hash of the packed puzzle, parity marks and rules, by `commit_puzzle`
+    #[default]
+    Flat,
+    /// Merkle root over the rows, by `merkle::merkle_root`, so rows can be revealed one at a time.
+    /// The rules are left to the keys.
+    Merkle,
+}
+
+impl Commitment {
+    pub fn commit<const N: usize, F: PrimeField + Absorb>(
+        &self,
+        poseidon_config: &PoseidonConfig<F>,
+        puzzle: &Puzzle<N>,
+        parity: &Parity<N>,
+        rules: &[ExtraRule],
+        salt: F,
+    ) -> F {
+        match self {
+            Commitment::Flat => commit_puzzle(poseidon_config, puzzle, parity, rules, salt),
+            Commitment::Merkle => merkle_root(poseidon_config, puzzle, parity, salt),
+        }
+    }
+
+    pub fn commit_var<const N: usize, F: PrimeField + Absorb>(
+        &self,
+        params: &CRHParametersVar<F>,
+        puzzle: &GridVar<N, F>,
+        parity: &GridVar<N, F>,
+        rules: &[ExtraRule],
+        salt: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        match self {
+            Commitment::Flat => commit_puzzle_var(params, puzzle, parity, rules, salt),
+            Commitment::Merkle => merkle_root_var(params, puzzle, parity, salt),
+        }
+    }
+}
+
+/// Commitment to a puzzle in the circuit, packed as by `commit_puzzle`
+pub fn commit_puzzle_var<const N: usize, F: PrimeField + Absorb>(
+    params: &CRHParametersVar<F>,
+    puzzle: &GridVar<N, F>,
+    parity: &GridVar<N, F>,
+    rules: &[ExtraRule],
+    salt: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let rule_bytes = rules
+        .iter()
+        .flat_map(|rule| rule.to_bytes())
+        .map(UInt8::constant)
+        .collect::<Vec<UInt8<F>>>();
+    let cells = puzzle.iter().flatten().map(|x| (x, cell_bits(N)));
+    let marks = parity.iter().flatten().map(|x| (x, PARITY_BITS));
+    let rule_bytes = rule_bytes.iter().map(|x| (x, 8));
+    let mut x = pack_var(&cells.chain(marks).chain(rule_bytes).collect::<Vec<_>>())?;
+    x.push(salt.clone());
+    CRHGadget::<F>::evaluate(params, &x)
+}
+
+/// Commitment to a solution, so it can be proved now and revealed later
+pub fn commit_solution<const N: usize, F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    solution: &Solution<N>,
+    salt: F,
+) -> F {
+    let cells = solution.0.into_iter().flatten().map(|x| (x, cell_bits(N)));
+    let mut x = pack::<F>(&cells.collect::<Vec<_>>());
+    x.push(salt);
+    CRH::<F>::evaluate(poseidon_config, x).unwrap()
+}
+
+/// Commitment to a solution in the circuit, packed as by `commit_solution`
+pub fn commit_solution_var<const N: usize, F: PrimeField + Absorb>(
+    params: &CRHParametersVar<F>,
+    solution: &GridVar<N, F>,
+    salt: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let cells = solution.iter().flatten().map(|x| (x, cell_bits(N)));
+    let mut x = pack_var(&cells.collect::<Vec<_>>())?;
+    x.push(salt.clone());
+    CRHGadget::<F>::evaluate(params, &x)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Bls12_381 as E, Fr};
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+    use ark_serialize::CanonicalSerialize;
+
+    use crate::parity::Parity;
+    use crate::poseidon::mk_poseidon_config;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+    use ark_r1cs_std::{fields::fp::FpVar, prelude::AllocVar, R1CSVar};
+
+    use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use proptest::collection::vec;
+    use proptest::prelude::{any, ProptestConfig};
+    use proptest::test_runner::TestRunner;
+
+    use crate::merkle::merkle_root;
+    use crate::parity::{ParityVar, EVEN};
+    use crate::poseidon::rand_poseidon_config;
+    use crate::rules::{ExtraRule, Thermometer};
+    use crate::Commitment;
+    use crate::{commit_puzzle, commit_puzzle_var, commit_solution, commit_solution_var};
+    use crate::{
+        mk_proof, mk_salt, mk_sudoku, pack_solver, rerandomize, setup, verify_proof, SOLVER_BYTES,
+    };
+    use crate::{Grid, PuzSol, Puzzle, PuzzleVar, Solution, SolutionVar, Sudoku};
+
+    #[test]
+    fn test_solver_binding() {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let (pk, vk) = setup::<2, E>(&poseidon_config, &[], Commitment::Flat);
+        let pvk = prepare_verifying_key(&vk);
+
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let mut sudoku = mk_sudoku::<2, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            Fr::from(0),
+            Fr::from(0),
+        );
+        sudoku.solver = Some(pack_solver(&[7; 57]));
+        let proof = mk_proof(&pk, &sudoku);
+
+        let mut inputs = sudoku.public_inputs();
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
+        inputs[2..].copy_from_slice(&pack_solver::<Fr>(&[8; 28]));
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
+    }
+
+    #[test]
+    fn test_pack_solver_collisions() {
+        assert_ne!(pack_solver::<Fr>(&[0, 7]), pack_solver::<Fr>(&[7]));
+        assert_ne!(pack_solver::<Fr>(&[]), pack_solver::<Fr>(&[0]));
+        let mut padded = vec![9; 31];
+        padded.push(0);
+        assert_ne!(pack_solver::<Fr>(&[9; 31]), pack_solver::<Fr>(&padded));
+        assert_ne!(pack_solver::<Fr>(&[0; 30]), pack_solver::<Fr>(&[0; 31]));
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let (pk, vk) = setup::<2, E>(&poseidon_config, &[], Commitment::Flat);
+        let pvk = prepare_verifying_key(&vk);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let sudoku = mk_sudoku::<2, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            mk_salt(),
+            mk_salt(),
+        );
+        let inputs = sudoku.public_inputs();
+        let proof = mk_proof(&pk, &sudoku);
+        let fresh = rerandomize(&vk, &proof);
+
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &fresh).unwrap());
+        let bytes = |proof: &Proof<E>| {
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        assert_ne!(bytes(&fresh), bytes(&proof));
+        assert_ne!(bytes(&fresh), bytes(&rerandomize(&vk, &proof)));
+    }
+
+    #[test]
+    fn test_bn254() {
+        // The same circuit proves over BN254, for EVM verifiers
+        use ark_bn254::{Bn254, Fr};
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let (pk, vk) = setup::<2, Bn254>(&poseidon_config, &[], Commitment::Flat);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let mut sudoku = mk_sudoku::<2, Bn254>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            mk_salt(),
+            mk_salt(),
+        );
+        sudoku.solver = Some(pack_solver(&[0xff; SOLVER_BYTES]));
+        let proof = mk_proof(&pk, &sudoku);
+        assert!(verify_proof(&vk, &sudoku.public_inputs(), &proof));
+
+        let mut inputs = sudoku.public_inputs();
+        inputs[0] += Fr::from(1);
+        assert!(!verify_proof(&vk, &inputs, &proof));
+    }
+
+    #[test]
+    fn test_puzzle_commitment() {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let sudoku = mk_sudoku::<2, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            mk_salt(),
+            mk_salt(),
+        );
+        let satisfied = |sudoku: Sudoku<2, Fr>| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            sudoku.generate_constraints(cs.clone()).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        assert!(satisfied(sudoku.clone()));
+
+        // The public hash must be of this puzzle, with these rules and this salt
+        let mut wrong = sudoku.clone();
+        wrong.hash = Some(Fr::from(0));
+        assert!(!satisfied(wrong));
+        let mut wrong = sudoku.clone();
+        wrong.rules = vec![ExtraRule::Thermometer(Thermometer {
+            path: vec![(0, 0), (0, 1)],
+        })];
+        assert!(!satisfied(wrong));
+        let mut wrong = sudoku;
+        wrong.salt = Some(Fr::from(0));
+        assert!(!satisfied(wrong));
+    }
+
+    #[test]
+    fn test_solution_commitment() {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let salt = mk_salt::<Fr>();
+        let sudoku = mk_sudoku::<2, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            Fr::from(0),
+            salt,
+        );
+        assert_eq!(
+            sudoku.solution_hash,
+            Some(commit_solution(&poseidon_config, &example.solution, salt))
+        );
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        sudoku.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // The commitment must be to this solution, with this salt
+        let mut wrong = sudoku;
+        wrong.solution_salt = Some(Fr::from(0));
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        wrong.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_merkle_commitment() {
+        let poseidon_config = mk_poseidon_config::<Fr>(4);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]]),
+            solution: Solution([[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]]),
+        };
+        let salt = mk_salt::<Fr>();
+        let sudoku = mk_sudoku::<4, E>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Merkle,
+            salt,
+            Fr::from(0),
+        );
+        assert_eq!(
+            sudoku.hash,
+            Some(merkle_root(
+                &poseidon_config,
+                &example.puzzle,
+                &Parity::default(),
+                salt
+            ))
+        );
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        sudoku.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // The flat hash is not a root
+        let mut wrong = sudoku;
+        wrong.hash = Some(commit_puzzle(
+            &poseidon_config,
+            &example.puzzle,
+            &Parity::default(),
+            &[],
+            salt,
+        ));
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        wrong.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    /// Native and gadget commitments agree
+    fn check_commitments<const N: usize>(
+        poseidon_config: &PoseidonConfig<Fr>,
+        puzzle: Puzzle<N>,
+        parity: Parity<N>,
+        solution: Solution<N>,
+        rules: &[ExtraRule],
+        salt: Fr,
+    ) {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let params = CRHParametersVar::new_witness(cs.clone(), || Ok(poseidon_config)).unwrap();
+        let puzzle_var = PuzzleVar::<N, Fr>::new_witness(cs.clone(), || Ok(puzzle.0)).unwrap();
+        let parity_var = ParityVar::<N, Fr>::new_witness(cs.clone(), || Ok(parity.0)).unwrap();
+        let solution_var =
+            SolutionVar::<N, Fr>::new_witness(cs.clone(), || Ok(solution.0)).unwrap();
+        let salt_var = FpVar::new_witness(cs.clone(), || Ok(salt)).unwrap();
+
+        let hash = commit_puzzle_var(&params, &puzzle_var.0, &parity_var.0, rules, &salt_var);
+        assert_eq!(
+            hash.unwrap().value().unwrap(),
+            commit_puzzle(poseidon_config, &puzzle, &parity, rules, salt)
+        );
+        let hash = commit_solution_var(&params, &solution_var.0, &salt_var);
+        assert_eq!(
+            hash.unwrap().value().unwrap(),
+            commit_solution(poseidon_config, &solution, salt)
+        );
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    fn fixed_commitments<const N: usize>() {
+        let puzzle = Puzzle(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i * j) % (N + 1)) as u8)
+        }));
+        let parity = Parity(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i + j) % 3) as u8)
+        }));
+        let solution = Solution(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i + j) % N + 1) as u8)
+        }));
+        let rules = [ExtraRule::Thermometer(Thermometer {
+            path: vec![(0, 0), (0, 1)],
+        })];
+        let poseidon_config = mk_poseidon_config::<Fr>(N);
+        check_commitments::<N>(
+            &poseidon_config,
+            puzzle,
+            parity,
+            solution,
+            &rules,
+            mk_salt(),
+        );
+    }
+
+    fn to_grid<const N: usize>(cells: &[u8]) -> Grid<N> {
+        std::array::from_fn(|i| std::array::from_fn(|j| cells[i * N + j]))
+    }
+
+    /// Random puzzles, marks, thermometers, salts and Poseidon configs
+    fn random_commitments<const N: usize>(cases: u32) {
+        let n = N as u8;
+        let strategy = (
+            any::<u64>(),
+            2_usize..10,
+            vec(0..=n, N * N),
+            vec(0..=EVEN, N * N),
+            vec(1..=n, N * N),
+            vec((0..N, 0..N), 0..4),
+        );
+        TestRunner::new(ProptestConfig::with_cases(cases))
+            .run(&strategy, |(seed, width, cells, marks, digits, path)| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let poseidon_config = rand_poseidon_config(width, &mut rng);
+                let rules = match path.is_empty() {
+                    true => vec![],
+                    false => vec![ExtraRule::Thermometer(Thermometer { path })],
+                };
+                check_commitments::<N>(
+                    &poseidon_config,
+                    Puzzle(to_grid(&cells)),
+                    Parity(to_grid(&marks)),
+                    Solution(to_grid(&digits)),
+                    &rules,
+                    Fr::rand(&mut rng),
+                );
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    /// Grids of gadgets are big arrays, too big for the default stack at 25x25
+    fn with_big_stack(f: impl FnOnce() + Send + 'static) {
+        std::thread::Builder::new()
+            .stack_size(64 << 20)
+            .spawn(f)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_commitments() {
+        with_big_stack(|| {
+            fixed_commitments::<2>();
+            fixed_commitments::<4>();
+            fixed_commitments::<9>();
+            fixed_commitments::<16>();
+            fixed_commitments::<25>();
+        });
+    }
+
+    #[test]
+    fn test_random_commitments() {
+        with_big_stack(|| {
+            random_commitments::<2>(64);
+            random_commitments::<4>(64);
+            random_commitments::<9>(32);
+            random_commitments::<16>(16);
+            random_commitments::<25>(16);
+        });
+    }
 }
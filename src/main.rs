@@ -1,35 +1,134 @@
+use std::any::Any;
+use std::collections::BTreeMap;
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
 
-use ark_bls12_381::Bls12_381 as E;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_crypto_primitives::sponge::{poseidon::PoseidonConfig, Absorb};
-use ark_ec::bls12::Bls12;
 use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
-use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
-mod poseidon;
-mod ss_serde;
-// TODO: Sort this org out
-use crate::poseidon::mk_poseidon_config;
-use crate::ss_serde::ProofHexed;
-use crate::ss_serde::VkHexed;
-use crate::ss_serde::{PoseidonConfigDef, PubInputs};
-use sudoku_snark::hash_puzzle;
+use sudoku_snark::aiken;
+use sudoku_snark::batch;
+use sudoku_snark::hint::{self, mk_hint};
+use sudoku_snark::merkle::{self, RowOpening};
+use sudoku_snark::parity::{read_parity, Parity};
+use sudoku_snark::plutus;
+use sudoku_snark::poseidon::mk_poseidon_config;
+use sudoku_snark::progress::{self, Progress};
+use sudoku_snark::rules::{read_rules, write_rules, Cell, ExtraRule};
+use sudoku_snark::samurai::{self, mk_samurai, read_samurai};
+use sudoku_snark::solidity;
+use sudoku_snark::solver::count_solutions;
+use sudoku_snark::ss_serde::ProofHexed;
+use sudoku_snark::ss_serde::VkHexed;
+use sudoku_snark::ss_serde::{
+    canonical_dec_to_field, dec_to_field, field_to_dec, hex_vec, unhex, Certificate, HintInputs,
+    PoseidonConfigDef, ProgressInputs, PubInputs, RowOpeningDef, Salt, SnarkjsPoint, SnarkjsProof,
+    SnarkjsVk,
+};
+use sudoku_snark::ss_serde::{EncodedPoint, Encoding};
+use sudoku_snark::{
+    blank_sudoku, commit_solution, mk_salt, pack_solver, rerandomize, verify_proof, SOLVER_BYTES,
+};
 use sudoku_snark::{check_proof, mk_proof, mk_sudoku, read_grid, setup, PuzSol, Puzzle, Solution};
+use sudoku_snark::{Commitment, Sudoku};
 
-use clap::{Parser, Subcommand};
-
-type F = <Bls12<ark_bls12_381::Config> as Pairing>::ScalarField;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 // WARNING:: Damn. This dimension has to be set at compile time
 // We could compile for multiple values, but for now just mod this
 const DIM: usize = 2;
 
+/// Pairing the keys are generated over, recorded with them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum Curve {
+    /// As checked by the Plutus builtins
+    #[serde(rename = "bls12-381")]
+    #[value(name = "bls12-381")]
+    Bls12381,
+    /// As checked by the EVM precompiles
+    Bn254,
+}
+
+impl Curve {
+    /// Name snarkjs gives the curve
+    fn snarkjs(self) -> &'static str {
+        match self {
+            Curve::Bls12381 => "bls12381",
+            Curve::Bn254 => "bn128",
+        }
+    }
+}
+
+/// What keys were generated for and by, with a SHA-256 fingerprint of each of their files,
+/// written last by init so later commands can check the keys still fit
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    /// `sudoku` or `samurai`
+    circuit: String,
+    /// Side of the grid, or of each sub-grid of a samurai
+    size: usize,
+    curve: Curve,
+    commitment: Commitment,
+    rules: Vec<ExtraRule>,
+    /// Version of sudoku-snark that generated the keys
+    version: String,
+    /// Hex SHA-256 of the circuit's constraint matrices and sizes, as `circuit_fingerprint`
+    #[serde(default)]
+    constraints: String,
+    /// Hex SHA-256 of each file, by name
+    files: BTreeMap<String, String>,
+}
+
+/// Files of the keys the manifest fingerprints, where they are present
+const MANIFEST_FILES: [&str; 8] = [
+    "pk.bin",
+    "vk.bin",
+    "params.json",
+    "poseidon_config.json",
+    "rules.json",
+    "commitment.json",
+    "curve.json",
+    "encoding.json",
+];
+
+/// Run `$body` with `$e` the pairing of `$curve`
+macro_rules! on_curve {
+    ($curve:expr, $e:ident => $body:expr) => {
+        match $curve {
+            Curve::Bls12381 => {
+                type $e = Bls12_381;
+                $body
+            }
+            Curve::Bn254 => {
+                type $e = Bn254;
+                $body
+            }
+        }
+    };
+}
+
 #[derive(Parser, Debug)]
 #[clap(author = "waalge", version, about)]
 /// Generate setups and proofs
 struct Arguments {
+    /// Threads for setup and proving with the parallel feature, and for the games of prove-batch,
+    /// which shares them out between its games. Defaults to one per core.
+    #[clap(long, global = true)]
+    threads: Option<usize>,
     #[clap(subcommand)]
     cmd: SubCommand,
 }
@@ -40,8 +139,228 @@ enum SubCommand {
     Init {
         /// Determines where keys are output
         keys: String,
+        /// Json file of variant rules the keys will enforce
+        #[clap(long)]
+        rules: Option<String>,
+        /// Commit to puzzles by a Merkle tree over rows, so rows can be revealed one at a time
+        #[clap(long)]
+        merkle: bool,
+        /// Pairing to generate the keys over
+        #[clap(long, value_enum, default_value = "bls12-381")]
+        curve: Curve,
+        /// Encoding of the points in params.json and the proofs
+        #[clap(long, value_enum, default_value = "compressed")]
+        encoding: Encoding,
     },
     // Generate the public inputs?
+    Set {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        puzzle: String,
+        /// Blind the commitment with a random salt, written next to the puzzle
+        #[clap(long)]
+        salted: bool,
+        /// Set the puzzle even if it has no solution or more than one
+        #[clap(long)]
+        allow_ambiguous: bool,
+        /// Replace an existing salt, or with no --salted drop it,
+        /// so datums set with it can no longer be opened
+        #[clap(long)]
+        force: bool,
+    },
+    /// Check the puzzle has exactly one solution, writing a certificate next to it
+    Certify {
+        /// which puzzle
+        puzzle: String,
+    },
+    /// Create proof
+    Prove {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+        /// Hex bytes identifying the solver, eg. a public key hash, that the proof is bound to
+        #[clap(long, default_value = "", value_parser = parse_solver)]
+        solver: String,
+        /// Blind the solution commitment with a random salt, written next to the proof
+        #[clap(long)]
+        salted: bool,
+    },
+    /// Prove many puzzles with one load of the proving key, in parallel.
+    /// With no puzzles, proves every sudoku with a solution.
+    ProveBatch {
+        /// which keys
+        keys: String,
+        /// which puzzles
+        sudokus: Vec<String>,
+        /// Hex bytes identifying the solver, bound to every proof
+        #[clap(long, default_value = "", value_parser = parse_solver)]
+        solver: String,
+        /// Blind each solution commitment with a fresh random salt
+        #[clap(long)]
+        salted: bool,
+    },
+    /// Check the proofs of many puzzles against their datums, all at once.
+    /// With no puzzles, checks every sudoku proved with the keys.
+    VerifyBatch {
+        /// which keys
+        keys: String,
+        /// which puzzles
+        sudokus: Vec<String>,
+    },
+    /// Replace the proof with a fresh one of the same statement, so reposts cannot be linked
+    Rerandomize {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+    },
+    /// Write a Solidity verifier for BN254 keys, and the calldata of each proof to call it with.
+    /// With no puzzles, writes calldata for every sudoku proved with the keys.
+    ExportSolidity {
+        /// which keys
+        keys: String,
+        /// which puzzles
+        sudokus: Vec<String>,
+    },
+    /// Write an Aiken module of the verifying key of BLS12-381 keys, for the validator to depend on
+    ExportPlutus {
+        /// which keys
+        keys: String,
+    },
+    /// Write the verifying key, and each proof with its public signals, as snarkjs does.
+    /// With no puzzles, writes every sudoku proved with the keys.
+    ExportSnarkjs {
+        /// which keys
+        keys: String,
+        /// which puzzles
+        sudokus: Vec<String>,
+    },
+    /// Read a proof and public signals written as snarkjs does, replacing the redeemer and datum
+    ImportSnarkjs {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+    },
+    /// Check a revealed puzzle and salt against the datum
+    Open {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        puzzle: String,
+    },
+    /// Reveal one row of a Merkle committed puzzle, with its path to the root
+    OpenRow {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        puzzle: String,
+        /// which row, from 0
+        row: usize,
+    },
+    /// Check a revealed row against the datum
+    VerifyRow {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        puzzle: String,
+        /// which row, from 0
+        row: usize,
+    },
+    /// Check a revealed solution and salt against the datum
+    Reveal {
+        /// which keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+    },
+    /// Samurai sudoku: five overlapping 9x9 grids
+    Samurai {
+        #[clap(subcommand)]
+        cmd: SamuraiCommand,
+    },
+    /// Hints: prove the value of one cell of a committed solution
+    Hint {
+        #[clap(subcommand)]
+        cmd: HintCommand,
+    },
+    /// Progress: prove how many cells of a partial solution are filled, without revealing them
+    Progress {
+        #[clap(subcommand)]
+        cmd: ProgressCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProgressCommand {
+    /// Generate the progress proof and verifier keys, alongside the sudoku keys
+    Init {
+        /// which sudoku keys
+        keys: String,
+    },
+    /// Prove the grid so far, read from progress.ssv with 0 for an empty cell
+    Prove {
+        /// which sudoku keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+    },
+    /// Check a progress proof against its datum
+    Verify {
+        /// which sudoku keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HintCommand {
+    /// Generate the hint proof and verifier keys, alongside the sudoku keys
+    Init {
+        /// which sudoku keys
+        keys: String,
+    },
+    /// Prove the value of a cell
+    Prove {
+        /// which sudoku keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+        /// row of the cell, from 0
+        row: usize,
+        /// column of the cell, from 0
+        col: usize,
+    },
+    /// Check a hint against the datum
+    Verify {
+        /// which sudoku keys
+        keys: String,
+        /// which puzzle
+        sudoku: String,
+        /// row of the cell, from 0
+        row: usize,
+        /// column of the cell, from 0
+        col: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SamuraiCommand {
+    /// Generate the proof and verifier keys
+    Init {
+        /// Determines where keys are output
+        keys: String,
+        /// Pairing to generate the keys over
+        #[clap(long, value_enum, default_value = "bls12-381")]
+        curve: Curve,
+        /// Encoding of the points in params.json and the proofs
+        #[clap(long, value_enum, default_value = "compressed")]
+        encoding: Encoding,
+    },
+    /// Generate the public inputs
     Set {
         /// which keys
         keys: String,
@@ -54,124 +373,1190 @@ enum SubCommand {
         keys: String,
         /// which puzzle
         sudoku: String,
+        /// Hex bytes identifying the solver, eg. a public key hash, that the proof is bound to
+        #[clap(long, default_value = "", value_parser = parse_solver)]
+        solver: String,
     },
 }
 
+/// Check a `--solver` is hex of at most `SOLVER_BYTES` bytes, so proving can unhex it
+fn parse_solver(s: &str) -> Result<String, String> {
+    let hex = s.trim_start_matches("0x");
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("not a hex string".to_string());
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err("odd length hex string".to_string());
+    }
+    if hex.len() / 2 > SOLVER_BYTES {
+        return Err(format!("longer than {} bytes", SOLVER_BYTES));
+    }
+    Ok(s.to_string())
+}
+
 fn main() {
     let args = Arguments::parse();
+    // prove-batch shares its threads out between the games itself
+    if let (Some(threads), false) = (
+        args.threads,
+        matches!(args.cmd, SubCommand::ProveBatch { .. }),
+    ) {
+        set_threads(threads);
+    }
     match args.cmd {
-        SubCommand::Init { keys } => {
+        SubCommand::Init {
+            keys,
+            rules,
+            merkle,
+            curve,
+            encoding,
+        } => {
             let _ = fs::create_dir_all(keys_path(&keys, ""));
-            init::<DIM>(&keys).unwrap();
+            write_curve(&keys, curve);
+            write_encoding(&keys, encoding);
+            let rules = rules.map_or(vec![], |fp| load_rules::<DIM>(&fp));
+            let commitment = match merkle {
+                true => Commitment::Merkle,
+                false => Commitment::Flat,
+            };
+            on_curve!(curve, E => init::<DIM, E>(&keys, &rules, commitment)).unwrap();
+            if curve == Curve::Bls12381 {
+                export_plutus(&keys);
+            }
+            write_manifest(&keys, "sudoku", DIM);
             println!("Init {}", keys)
         }
-        SubCommand::Set { keys, puzzle } => {
+        SubCommand::Set {
+            keys,
+            puzzle,
+            salted,
+            allow_ambiguous,
+            force,
+        } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Circuit);
+            if !force && fs::metadata(sudoku_salt_path(&puzzle)).is_ok() {
+                let action = if salted { "replace" } else { "drop" };
+                println!(
+                    "puzzle already has a salt, needed to open its datum, pass --force to {} it",
+                    action
+                );
+                std::process::exit(1)
+            }
+            if !allow_ambiguous {
+                let solutions = certify::<DIM>(&puzzle);
+                if solutions != 1 {
+                    println!(
+                        "puzzle {}, pass --allow-ambiguous to set it anyway",
+                        describe_solutions(solutions)
+                    );
+                    std::process::exit(1)
+                }
+            }
             let _ = fs::create_dir_all(results_path(&keys, &puzzle, ""));
-            let _ = set::<DIM>(&keys, &puzzle);
+            let _ = on_curve!(read_curve(&keys), E => set::<DIM, E>(&keys, &puzzle, salted));
             println!("set")
         }
-        SubCommand::Prove { keys, sudoku } => {
+        SubCommand::Certify { puzzle } => {
+            let solutions = certify::<DIM>(&puzzle);
+            println!("puzzle {}", describe_solutions(solutions));
+            if solutions != 1 {
+                std::process::exit(1)
+            }
+        }
+        SubCommand::Prove {
+            keys,
+            sudoku,
+            solver,
+            salted,
+        } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Proving);
             let _ = fs::create_dir_all(results_path(&keys, &sudoku, ""));
-            let _ = prove::<DIM>(&keys, &sudoku);
+            let _ = on_curve!(read_curve(&keys), E => {
+                prove::<DIM, E>(&keys, &sudoku, &unhex(&solver), salted)
+            });
             println!("prove")
         }
+        SubCommand::ProveBatch {
+            keys,
+            sudokus,
+            solver,
+            salted,
+        } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Proving);
+            let sudokus = if sudokus.is_empty() {
+                sudokus_with(sudoku_solution_path)
+            } else {
+                sudokus
+            };
+            let failures = on_curve!(read_curve(&keys), E => {
+                prove_batch::<DIM, E>(&keys, &sudokus, &unhex(&solver), salted, args.threads)
+            });
+            println!("proved {} of {}", sudokus.len() - failures, sudokus.len());
+            if failures > 0 {
+                std::process::exit(1)
+            }
+        }
+        SubCommand::VerifyBatch { keys, sudokus } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            let sudokus = if sudokus.is_empty() {
+                sudokus_with(|sudoku| proof_hexed_path(&keys, sudoku))
+            } else {
+                sudokus
+            };
+            let bad = on_curve!(read_curve(&keys), E => verify_batch::<E>(&keys, &sudokus));
+            for (i, why) in &bad {
+                println!("{}: {}", sudokus[*i], why);
+            }
+            println!(
+                "verified {} of {}",
+                sudokus.len() - bad.len(),
+                sudokus.len()
+            );
+            if !bad.is_empty() {
+                std::process::exit(1)
+            }
+        }
+        SubCommand::Rerandomize { keys, sudoku } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            if on_curve!(read_curve(&keys), E => rerandomize_proof::<E>(&keys, &sudoku)) {
+                println!("rerandomize")
+            } else {
+                println!("proof does not match datum");
+                std::process::exit(1)
+            }
+        }
+        SubCommand::ExportSolidity { keys, sudokus } => {
+            check_any_manifest(&keys);
+            if read_curve(&keys) != Curve::Bn254 {
+                panic!("keys {} are not over BN254, which the EVM verifies", keys)
+            }
+            let sudokus = if sudokus.is_empty() {
+                sudokus_with(|sudoku| proof_hexed_path(&keys, sudoku))
+            } else {
+                sudokus
+            };
+            export_solidity(&keys, &sudokus);
+            println!("export solidity {}", keys)
+        }
+        SubCommand::ExportPlutus { keys } => {
+            check_any_manifest(&keys);
+            if read_curve(&keys) != Curve::Bls12381 {
+                panic!(
+                    "keys {} are not over BLS12-381, which Plutus verifies",
+                    keys
+                )
+            }
+            export_plutus(&keys);
+            println!("export plutus {}", keys)
+        }
+        SubCommand::ExportSnarkjs { keys, sudokus } => {
+            check_any_manifest(&keys);
+            let sudokus = if sudokus.is_empty() {
+                sudokus_with(|sudoku| proof_hexed_path(&keys, sudoku))
+            } else {
+                sudokus
+            };
+            let curve = read_curve(&keys);
+            on_curve!(curve, E => export_snarkjs::<E>(&keys, &sudokus, curve));
+            println!("export snarkjs {}", keys)
+        }
+        SubCommand::ImportSnarkjs { keys, sudoku } => {
+            check_any_manifest(&keys);
+            let curve = read_curve(&keys);
+            if on_curve!(curve, E => import_snarkjs::<E>(&keys, &sudoku, curve)) {
+                println!("import snarkjs")
+            } else {
+                println!("proof does not match datum");
+                std::process::exit(1)
+            }
+        }
+        SubCommand::Open { keys, puzzle } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            if on_curve!(read_curve(&keys), E => open::<DIM, E>(&keys, &puzzle)) {
+                println!("open")
+            } else {
+                println!("puzzle does not match datum");
+                std::process::exit(1)
+            }
+        }
+        SubCommand::OpenRow { keys, puzzle, row } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            on_curve!(read_curve(&keys), E => open_row::<DIM, E>(&keys, &puzzle, row));
+            println!("open row {}", row)
+        }
+        SubCommand::VerifyRow { keys, puzzle, row } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            if on_curve!(read_curve(&keys), E => verify_row::<E>(&keys, &puzzle, row)) {
+                println!("verify row {}", row)
+            } else {
+                println!("row does not match datum");
+                std::process::exit(1)
+            }
+        }
+        SubCommand::Reveal { keys, sudoku } => {
+            check_manifest(&keys, "sudoku", DIM, Needs::Verifying);
+            if on_curve!(read_curve(&keys), E => reveal::<DIM, E>(&keys, &sudoku)) {
+                println!("reveal")
+            } else {
+                println!("solution does not match datum");
+                std::process::exit(1)
+            }
+        }
+        SubCommand::Samurai { cmd } => match cmd {
+            SamuraiCommand::Init {
+                keys,
+                curve,
+                encoding,
+            } => {
+                let _ = fs::create_dir_all(keys_path(&keys, ""));
+                write_curve(&keys, curve);
+                write_encoding(&keys, encoding);
+                on_curve!(curve, E => samurai_init::<E>(&keys)).unwrap();
+                if curve == Curve::Bls12381 {
+                    export_plutus(&keys);
+                }
+                write_manifest(&keys, "samurai", 9);
+                println!("Init {}", keys)
+            }
+            SamuraiCommand::Set { keys, puzzle } => {
+                check_manifest(&keys, "samurai", 9, Needs::Circuit);
+                let _ = fs::create_dir_all(results_path(&keys, &puzzle, ""));
+                let _ = on_curve!(read_curve(&keys), E => samurai_set::<E>(&keys, &puzzle));
+                println!("set")
+            }
+            SamuraiCommand::Prove {
+                keys,
+                sudoku,
+                solver,
+            } => {
+                check_manifest(&keys, "samurai", 9, Needs::Proving);
+                let _ = fs::create_dir_all(results_path(&keys, &sudoku, ""));
+                let _ = on_curve!(
+                    read_curve(&keys),
+                    E => samurai_prove::<E>(&keys, &sudoku, &unhex(&solver))
+                );
+                println!("prove")
+            }
+        },
+        SubCommand::Hint { cmd } => match cmd {
+            HintCommand::Init { keys } => {
+                check_manifest(&keys, "sudoku", DIM, Needs::Circuit);
+                let _ = fs::create_dir_all(keys_path(&hint_keys(&keys), ""));
+                on_curve!(read_curve(&keys), E => hint_init::<DIM, E>(&keys));
+                write_manifest(&keys, "hint", DIM);
+                println!("Init hint {}", keys)
+            }
+            HintCommand::Prove {
+                keys,
+                sudoku,
+                row,
+                col,
+            } => {
+                check_manifest(&keys, "hint", DIM, Needs::Proving);
+                let _ = fs::create_dir_all(results_path(&keys, &sudoku, ""));
+                let value = on_curve!(read_curve(&keys), E => {
+                    hint_prove::<DIM, E>(&keys, &sudoku, (row, col))
+                });
+                println!("hint ({}, {}) is {}", row, col, value)
+            }
+            HintCommand::Verify {
+                keys,
+                sudoku,
+                row,
+                col,
+            } => {
+                check_manifest(&keys, "hint", DIM, Needs::Verifying);
+                if on_curve!(read_curve(&keys), E => hint_verify::<E>(&keys, &sudoku, (row, col))) {
+                    println!("verify hint")
+                } else {
+                    println!("hint does not match datum");
+                    std::process::exit(1)
+                }
+            }
+        },
+        SubCommand::Progress { cmd } => match cmd {
+            ProgressCommand::Init { keys } => {
+                check_manifest(&keys, "sudoku", DIM, Needs::Circuit);
+                let _ = fs::create_dir_all(keys_path(&progress_keys(&keys), ""));
+                on_curve!(read_curve(&keys), E => progress_init::<DIM, E>(&keys));
+                write_manifest(&keys, "progress", DIM);
+                println!("Init progress {}", keys)
+            }
+            ProgressCommand::Prove { keys, sudoku } => {
+                check_manifest(&keys, "progress", DIM, Needs::Proving);
+                let _ = fs::create_dir_all(results_path(&keys, &sudoku, ""));
+                let filled =
+                    on_curve!(read_curve(&keys), E => progress_prove::<DIM, E>(&keys, &sudoku));
+                println!("progress of {} cells", filled)
+            }
+            ProgressCommand::Verify { keys, sudoku } => {
+                check_manifest(&keys, "progress", DIM, Needs::Verifying);
+                if on_curve!(read_curve(&keys), E => progress_verify::<E>(&keys, &sudoku)) {
+                    println!("verify progress")
+                } else {
+                    println!("progress does not match datum");
+                    std::process::exit(1)
+                }
+            }
+        },
     }
 }
 
-fn init<const N: usize>(name: &str) -> Result<(), serde_json::Error> {
+fn init<const N: usize, E>(
+    name: &str,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
     // Write poseidon config
-    write_poseidon_config::<F>(name, N);
-    let poseidon_config = read_poseidon_config::<F>(name);
+    write_poseidon_config::<E::ScalarField>(name, N);
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(name);
+
+    // Write rules
+    write_rules(&keys_rules_path(name), rules).unwrap();
+    fs::write(
+        keys_commitment_path(name),
+        serde_json::to_string_pretty(&commitment)?,
+    )
+    .unwrap();
 
     // Setup
-    let (pk, vk) = setup::<N, E>(&poseidon_config);
+    let (pk, vk) = setup::<N, E>(&poseidon_config, rules, commitment);
 
-    // write pk
-    let mut v_pk = Vec::new();
-    pk.serialize_compressed(&mut v_pk).unwrap();
-    fs::write(pk_path(name), v_pk).unwrap();
+    write_keys(name, &pk, &vk, read_encoding(name));
+    Ok(())
+}
 
-    // write vk
-    let mut v_vk = Vec::new();
-    vk.serialize_compressed(&mut v_vk).unwrap();
-    fs::write(vk_path(name), v_vk).unwrap();
+fn set<const N: usize, E>(keys: &str, sud_path: &str, salted: bool) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = read_sudoku_rules::<N>(keys, sud_path);
 
-    // write vk_hexed
-    let vk_hexed = VkHexed::from(vk.clone());
+    // Propose problem (here with solution)
+    // Solver solves it (not here) and creates proof
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    // A fresh salt each time, so two commitments to the same puzzle cannot be linked,
+    // and none at all when unsalted, rather than one left by an earlier set
+    let salt = if salted {
+        let salt = mk_salt::<E::ScalarField>();
+        write_salt(sud_path, salt);
+        salt
+    } else {
+        let _ = fs::remove_file(sudoku_salt_path(sud_path));
+        E::ScalarField::zero()
+    };
+    let hash = read_commitment(keys).commit(&poseidon_config, &puzzle, &parity, &rules, salt);
+    let pub_inputs = PubInputs {
+        pub1: format!("{}", hash),
+        solver: vec![],
+        solution_hash: None,
+    };
+    write_datum(keys, sud_path, &pub_inputs);
+    Ok(())
+}
+
+/// Count the solutions of the puzzle, up to two, and write the certificate
+fn certify<const N: usize>(sud_path: &str) -> usize {
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    let rules = load_rules::<N>(&sudoku_rules_path(sud_path));
+    let solutions = count_solutions(&puzzle, &parity, &rules, 2);
+    let certificate = Certificate {
+        puzzle: puzzle.0.iter().map(|row| row.to_vec()).collect(),
+        parity: parity.0.iter().map(|row| row.to_vec()).collect(),
+        rules,
+        solutions,
+    };
     fs::write(
-        vk_hexed_path(name),
-        serde_json::to_string_pretty(&vk_hexed).unwrap(),
+        sudoku_certificate_path(sud_path),
+        serde_json::to_string_pretty(&certificate).unwrap(),
     )
     .unwrap();
+    solutions
+}
+
+fn describe_solutions(solutions: usize) -> &'static str {
+    match solutions {
+        0 => "has no solution",
+        1 => "has a unique solution",
+        _ => "has more than one solution",
+    }
+}
+
+fn prove<const N: usize, E>(
+    keys: &str,
+    sud_path: &str,
+    solver: &[u8],
+    salted: bool,
+) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    if salted {
+        write_solution_salt(keys, sud_path, mk_salt::<E::ScalarField>());
+    }
+    prove_with::<N, E>(&read_pk(keys), &read_vk(keys), keys, sud_path, solver)
+}
 
+fn prove_with<const N: usize, E>(
+    pk: &ProvingKey<E>,
+    vk: &VerifyingKey<E>,
+    keys: &str,
+    sud_path: &str,
+    solver: &[u8],
+) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let mut sudoku = read_sudoku::<N, E>(keys, sud_path);
+    sudoku.solver = Some(pack_solver(solver));
+
+    // The datum is the setter's, so the prover may add to it but not change the puzzle
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let datum: PubInputs = serde_json::from_str(&toj).unwrap();
+    if datum.pub1 != format!("{}", sudoku.hash.unwrap()) {
+        panic!("puzzle of {} does not match the setter's datum", sud_path)
+    }
+
+    let proof = mk_proof(pk, &sudoku);
+    write_redeemer(keys, sud_path, &proof);
+
+    // The datum now records who the proof is for, and the solution it commits to
+    let pub_inputs = PubInputs {
+        pub1: datum.pub1,
+        solver: sudoku.solver.unwrap().iter().map(field_to_dec).collect(),
+        solution_hash: sudoku.solution_hash.as_ref().map(field_to_dec),
+    };
+    write_datum(keys, sud_path, &pub_inputs);
+
+    check_proof::<E>(vk, &sudoku.public_inputs(), &proof);
     Ok(())
 }
 
-fn set<const N: usize>(keys: &str, sud_path: &str) -> Result<(), serde_json::Error> {
-    let poseidon_config = read_poseidon_config::<F>(keys);
-    let pk_bin: Vec<u8> = fs::read(&pk_path(keys)).unwrap();
-    let pk = ProvingKey::<E>::deserialize_with_mode(
-        &pk_bin[..],
-        ark_serialize::Compress::Yes,
-        ark_serialize::Validate::Yes,
+/// Prove each sudoku on a pool of threads sharing the keys, reporting each as it finishes.
+/// With the parallel feature, each worker proves on its own share of the threads.
+/// A failed sudoku does not stop the others. Returns the number that failed.
+fn prove_batch<const N: usize, E>(
+    keys: &str,
+    sudokus: &[String],
+    solver: &[u8],
+    salted: bool,
+    threads: Option<usize>,
+) -> usize
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let pk = read_pk(keys);
+    let vk = read_vk(keys);
+    let next = AtomicUsize::new(0);
+    let failures = AtomicUsize::new(0);
+    let threads = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let workers = threads.min(sudokus.len());
+    let share = threads / workers.max(1);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                with_threads(share, || {
+                    while let Some(sudoku) = sudokus.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let start = Instant::now();
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            let _ = fs::create_dir_all(results_path(keys, sudoku, ""));
+                            if salted {
+                                write_solution_salt(keys, sudoku, mk_salt::<E::ScalarField>());
+                            }
+                            prove_with::<N, E>(&pk, &vk, keys, sudoku, solver).unwrap()
+                        }));
+                        match result {
+                            Ok(()) => println!("{}: proved in {:.2?}", sudoku, start.elapsed()),
+                            Err(e) => {
+                                failures.fetch_add(1, Ordering::Relaxed);
+                                println!(
+                                    "{}: failed after {:.2?}: {}",
+                                    sudoku,
+                                    start.elapsed(),
+                                    panic_message(&*e)
+                                )
+                            }
+                        }
+                    }
+                })
+            });
+        }
+    });
+    failures.into_inner()
+}
+
+/// Size the pool arkworks runs setup and proving on
+#[cfg(feature = "parallel")]
+fn set_threads(threads: usize) {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .unwrap();
+}
+
+#[cfg(not(feature = "parallel"))]
+fn set_threads(_threads: usize) {
+    eprintln!("built without the parallel feature, so --threads is ignored");
+}
+
+/// Run `f` with arkworks' setup and proving on a pool of its own
+#[cfg(feature = "parallel")]
+fn with_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap()
+        .install(f)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn with_threads<T>(_threads: usize, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+fn panic_message(e: &(dyn Any + Send)) -> &str {
+    match (e.downcast_ref::<&str>(), e.downcast_ref::<String>()) {
+        (Some(s), _) => s,
+        (_, Some(s)) => s,
+        _ => "panicked",
+    }
+}
+
+/// Names of the sudokus having the file at `path`, in order
+fn sudokus_with(path: impl Fn(&str) -> String) -> Vec<String> {
+    let mut names = fs::read_dir(format!("{}/sudokus", out_root()))
+        .unwrap()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| fs::metadata(path(name)).is_ok())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Write the datum, as json and as Plutus Data
+fn write_datum(keys: &str, sud_path: &str, pub_inputs: &PubInputs) {
+    fs::write(
+        pub_inputs_path(keys, sud_path),
+        serde_json::to_string_pretty(pub_inputs).unwrap(),
     )
     .unwrap();
+    let datum = plutus::datum(pub_inputs);
+    fs::write(plutus_datum_path(keys, sud_path), datum.to_json()).unwrap();
+    fs::write(plutus_datum_cbor_path(keys, sud_path), datum.to_cbor()).unwrap();
+}
+
+/// Write the proof as the redeemer, as hex and as Plutus Data
+fn write_redeemer<E>(keys: &str, sud_path: &str, proof: &Proof<E>)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    fs::write(
+        proof_hexed_path(keys, sud_path),
+        serde_json::to_string_pretty(&ProofHexed::new(proof, read_encoding(keys))).unwrap(),
+    )
+    .unwrap();
+    let redeemer = plutus::redeemer(proof);
+    fs::write(plutus_redeemer_path(keys, sud_path), redeemer.to_json()).unwrap();
+    fs::write(
+        plutus_redeemer_cbor_path(keys, sud_path),
+        redeemer.to_cbor(),
+    )
+    .unwrap();
+}
+
+/// Public inputs in the datum of the sudoku, and its proof
+fn read_proved<E>(keys: &str, sud_path: &str) -> (Vec<E::ScalarField>, Proof<E>)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let pub_inputs: PubInputs = serde_json::from_str(&toj).unwrap();
+    let toj = fs::read_to_string(proof_hexed_path(keys, sud_path)).expect("Unable to read file");
+    let proof = serde_json::from_str::<ProofHexed>(&toj)
+        .unwrap()
+        .proof(read_encoding(keys));
+    (datum_inputs(&pub_inputs), proof)
+}
+
+/// Indices of the sudokus whose proof cannot be read or does not match their datum, with why.
+/// A proof that cannot be read does not stop the others being checked.
+fn verify_batch<E>(keys: &str, sudokus: &[String]) -> Vec<(usize, String)>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    let mut bad = vec![];
+    let mut read = vec![];
+    let mut batch = vec![];
+    for (i, sud_path) in sudokus.iter().enumerate() {
+        match panic::catch_unwind(|| read_proved::<E>(keys, sud_path)) {
+            Ok(proved) => {
+                read.push(i);
+                batch.push(proved);
+            }
+            Err(e) => bad.push((i, format!("cannot read proof: {}", panic_message(&*e)))),
+        }
+    }
+    for j in batch::verify_batch(&read_vk(keys), &batch) {
+        bad.push((read[j], "proof does not match datum".to_string()));
+    }
+    bad.sort();
+    bad
+}
+
+/// Write the verifier contract of the keys, and the calldata of each sudoku's proof
+fn export_solidity(keys: &str, sudokus: &[String]) {
+    let vk = read_vk::<Bn254>(keys);
+    fs::write(
+        solidity_verifier_path(keys),
+        solidity::verifier_contract(&vk),
+    )
+    .unwrap();
+    for sud_path in sudokus {
+        let (inputs, proof) = read_proved::<Bn254>(keys, sud_path);
+        fs::write(
+            calldata_path(keys, sud_path),
+            format!("0x{}", hex_vec(&solidity::calldata(&proof, &inputs))),
+        )
+        .unwrap();
+    }
+}
+
+/// Write the Aiken module of the verifying key
+fn export_plutus(keys: &str) {
+    let vk = read_vk::<Bls12_381>(keys);
+    fs::write(
+        aiken_module_path(keys),
+        aiken::verifying_key_module(keys, &vk),
+    )
+    .unwrap();
+}
+
+/// Rewrite the proof of the sudoku afresh, if it verifies against the datum
+fn rerandomize_proof<E>(keys: &str, sud_path: &str) -> bool
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    let (inputs, proof) = read_proved::<E>(keys, sud_path);
+    let vk = read_vk(keys);
+    if !verify_proof::<E>(&vk, &inputs, &proof) {
+        return false;
+    }
+    write_redeemer(keys, sud_path, &rerandomize(&vk, &proof));
+    true
+}
+
+/// Write the verifying key, and the proof and public inputs of each sudoku, in snarkjs' layout
+fn export_snarkjs<E>(keys: &str, sudokus: &[String], curve: Curve)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    let vk = SnarkjsVk::new(&read_vk::<E>(keys), curve.snarkjs());
+    fs::write(
+        snarkjs_vk_path(keys),
+        serde_json::to_string_pretty(&vk).unwrap(),
+    )
+    .unwrap();
+    for sud_path in sudokus {
+        let (inputs, proof) = read_proved::<E>(keys, sud_path);
+        fs::write(
+            snarkjs_proof_path(keys, sud_path),
+            serde_json::to_string_pretty(&SnarkjsProof::new(&proof, curve.snarkjs())).unwrap(),
+        )
+        .unwrap();
+        let public = inputs.iter().map(field_to_dec).collect::<Vec<_>>();
+        fs::write(
+            snarkjs_public_path(keys, sud_path),
+            serde_json::to_string_pretty(&public).unwrap(),
+        )
+        .unwrap();
+    }
+}
+
+/// Replace the proof and datum of the sudoku with those written in snarkjs' layout,
+/// if the proof holds under the keys for the setter's puzzle. The datum keeps the setter's `pub1`.
+fn import_snarkjs<E>(keys: &str, sud_path: &str, curve: Curve) -> bool
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    let toj = fs::read_to_string(snarkjs_proof_path(keys, sud_path)).expect("Unable to read file");
+    let proof: SnarkjsProof = serde_json::from_str(&toj).unwrap();
+    if proof.curve != curve.snarkjs() {
+        panic!(
+            "proof is over {}, but keys {} are over {}",
+            proof.curve,
+            keys,
+            curve.snarkjs()
+        )
+    }
+    let proof: Proof<E> = proof.into();
+    let toj = fs::read_to_string(snarkjs_public_path(keys, sud_path)).expect("Unable to read file");
+    let public: Vec<String> = serde_json::from_str(&toj).unwrap();
+    let inputs = public
+        .iter()
+        .map(|x| {
+            canonical_dec_to_field::<E::ScalarField>(x)
+                .unwrap_or_else(|e| panic!("public signal of {}: {}", sud_path, e))
+        })
+        .collect::<Vec<_>>();
+    let vk = read_vk::<E>(keys);
+    if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+        panic!(
+            "{} public signals, but keys {} take {}",
+            inputs.len(),
+            keys,
+            vk.gamma_abc_g1.len() - 1
+        )
+    }
+
+    // The setter's datum fixes the puzzle, so the proof must be of it
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let datum: PubInputs = serde_json::from_str(&toj).unwrap();
+    if public[0] != datum.pub1 {
+        panic!("puzzle of {} does not match the setter's datum", sud_path)
+    }
+    if !verify_proof(&vk, &inputs, &proof) {
+        return false;
+    }
+    let pub_inputs = PubInputs {
+        pub1: datum.pub1,
+        solution_hash: public.get(1).cloned(),
+        solver: public.iter().skip(2).cloned().collect(),
+    };
+    write_redeemer(keys, sud_path, &proof);
+    write_datum(keys, sud_path, &pub_inputs);
+    true
+}
+
+/// Public inputs recorded in a datum, in the order the circuit allocates them
+fn datum_inputs<F: PrimeField>(pub_inputs: &PubInputs) -> Vec<F> {
+    std::iter::once(&pub_inputs.pub1)
+        .chain(&pub_inputs.solution_hash)
+        .chain(&pub_inputs.solver)
+        .map(|x| dec_to_field(x))
+        .collect()
+}
+
+/// The sudoku with its solution and salts, ready to prove
+fn read_sudoku<const N: usize, E>(keys: &str, sud_path: &str) -> Sudoku<N, E::ScalarField>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = read_sudoku_rules::<N>(keys, sud_path);
 
     // Propose problem (here with solution)
     // Solver solves it (not here) and creates proof
-    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(&sud_path)));
-    let hash = hash_puzzle(&poseidon_config, &puzzle);
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    let solution = Solution(read_grid::<N>(&sudoku_solution_path(sud_path)));
+    mk_sudoku::<N, E>(
+        &poseidon_config,
+        &PuzSol { puzzle, solution },
+        &parity,
+        &rules,
+        read_commitment(keys),
+        read_salt(sud_path),
+        read_solution_salt(keys, sud_path),
+    )
+}
+
+fn open<const N: usize, E>(keys: &str, sud_path: &str) -> bool
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = read_sudoku_rules::<N>(keys, sud_path);
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    let hash = read_commitment(keys).commit(
+        &poseidon_config,
+        &puzzle,
+        &parity,
+        &rules,
+        read_salt(sud_path),
+    );
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let pub_inputs: PubInputs = serde_json::from_str(&toj).unwrap();
+    pub_inputs.pub1 == format!("{}", hash)
+}
+
+fn open_row<const N: usize, E>(keys: &str, sud_path: &str, row: usize)
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    if read_commitment(keys) != Commitment::Merkle {
+        panic!("keys {} do not commit to puzzles by rows", keys)
+    }
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    let opening = merkle::open_row(&poseidon_config, &puzzle, &parity, read_salt(sud_path), row)
+        .unwrap_or_else(|e| panic!("cannot open {}: {}", sud_path, e));
+    fs::write(
+        row_opening_path(keys, sud_path, row),
+        serde_json::to_string_pretty(&RowOpeningDef::from(opening)).unwrap(),
+    )
+    .unwrap();
+}
+
+fn verify_row<E>(keys: &str, sud_path: &str, row: usize) -> bool
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let toj =
+        fs::read_to_string(row_opening_path(keys, sud_path, row)).expect("Unable to read file");
+    let opening: RowOpening<E::ScalarField> =
+        serde_json::from_str::<RowOpeningDef>(&toj).unwrap().into();
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let pub_inputs: PubInputs = serde_json::from_str(&toj).unwrap();
+    let root = dec_to_field(&pub_inputs.pub1);
+    opening.index == row && merkle::verify_row(&poseidon_config, root, &opening)
+}
+
+fn reveal<const N: usize, E>(keys: &str, sud_path: &str) -> bool
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let solution = Solution(read_grid::<N>(&sudoku_solution_path(sud_path)));
+    let solution_hash = commit_solution(
+        &poseidon_config,
+        &solution,
+        read_solution_salt(keys, sud_path),
+    );
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let pub_inputs: PubInputs = serde_json::from_str(&toj).unwrap();
+    pub_inputs.solution_hash == Some(field_to_dec(&solution_hash))
+}
+
+fn samurai_init<E>(name: &str) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    // Sub-grids are 9x9, so size the poseidon config as for a 9x9 sudoku
+    write_poseidon_config::<E::ScalarField>(name, 9);
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(name);
+    let (pk, vk) = samurai::setup::<E>(&poseidon_config);
+    write_keys(name, &pk, &vk, read_encoding(name));
+    Ok(())
+}
+
+fn samurai_set<E>(keys: &str, sud_path: &str) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let puzzle = Puzzle(read_samurai(&samurai_puzzle_path(sud_path)));
+    let hash = samurai::hash_puzzle(&poseidon_config, &puzzle);
     let pub_inputs = PubInputs {
         pub1: format!("{}", hash),
+        solver: vec![],
+        solution_hash: None,
+    };
+    write_datum(keys, sud_path, &pub_inputs);
+    Ok(())
+}
+
+fn samurai_prove<E>(keys: &str, sud_path: &str, solver: &[u8]) -> Result<(), serde_json::Error>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let pk = read_pk(keys);
+
+    let puzzle = Puzzle(read_samurai(&samurai_puzzle_path(sud_path)));
+    let solution = Solution(read_samurai(&samurai_solution_path(sud_path)));
+    let mut samurai = mk_samurai::<E>(&poseidon_config, &PuzSol { puzzle, solution });
+    samurai.solver = Some(pack_solver(solver));
+
+    // As for sudokus, the prover may add to the setter's datum but not change the puzzle
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let datum: PubInputs = serde_json::from_str(&toj).unwrap();
+    if datum.pub1 != format!("{}", samurai.hash.unwrap()) {
+        panic!("puzzle of {} does not match the setter's datum", sud_path)
+    }
+
+    let proof = mk_proof(&pk, &samurai);
+    write_redeemer(keys, sud_path, &proof);
+
+    let pub_inputs = PubInputs {
+        pub1: datum.pub1,
+        solver: samurai.solver.unwrap().iter().map(field_to_dec).collect(),
+        solution_hash: None,
     };
+    write_datum(keys, sud_path, &pub_inputs);
+
+    let vk = read_vk(keys);
+    check_proof::<E>(&vk, &samurai.public_inputs(), &proof);
+    Ok(())
+}
+
+/// Hint keys live with the sudoku keys whose hashes they share
+fn hint_keys(keys: &str) -> String {
+    format!("{}/hint", keys)
+}
+
+fn hint_init<const N: usize, E>(keys: &str)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = load_rules::<N>(&keys_rules_path(keys));
+    let (pk, vk) = hint::setup::<N, E>(&poseidon_config, &rules, read_commitment(keys));
+    write_keys(&hint_keys(keys), &pk, &vk, read_encoding(keys));
+}
+
+fn hint_prove<const N: usize, E>(keys: &str, sud_path: &str, cell: Cell) -> u8
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let pk = read_pk(&hint_keys(keys));
+    let hint = mk_hint(&read_sudoku::<N, E>(keys, sud_path), cell);
+
+    let proof = mk_proof(&pk, &hint);
     fs::write(
-        pub_inputs_path(keys, sud_path),
-        serde_json::to_string_pretty(&pub_inputs).unwrap(),
+        hint_proof_path(keys, sud_path, cell),
+        serde_json::to_string_pretty(&ProofHexed::new(&proof, read_encoding(keys))).unwrap(),
     )
     .unwrap();
-    Ok(())
+    let hint_inputs = HintInputs {
+        pub1: field_to_dec(&hint.hash.unwrap()),
+        solution_hash: field_to_dec(&hint.solution_hash.unwrap()),
+        row: cell.0,
+        col: cell.1,
+        value: hint.value.unwrap(),
+    };
+    fs::write(
+        hint_inputs_path(keys, sud_path, cell),
+        serde_json::to_string_pretty(&hint_inputs).unwrap(),
+    )
+    .unwrap();
+
+    let vk = read_vk(&hint_keys(keys));
+    check_proof::<E>(&vk, &hint.public_inputs(), &proof);
+    hint.value.unwrap()
 }
 
-fn prove<const N: usize>(keys: &str, sud_path: &str) -> Result<(), serde_json::Error> {
-    let poseidon_config = read_poseidon_config::<F>(keys);
-    let pk_bin: Vec<u8> = fs::read(&pk_path(keys)).unwrap();
-    let pk = ProvingKey::<E>::deserialize_with_mode(
-        &pk_bin[..],
-        ark_serialize::Compress::Yes,
-        ark_serialize::Validate::Yes,
+/// Whether the hint is for the puzzle and solution hashes in the datum
+fn hint_verify<E>(keys: &str, sud_path: &str, cell: Cell) -> bool
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    let toj = fs::read_to_string(pub_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let pub_inputs: PubInputs = serde_json::from_str(&toj).unwrap();
+    let toj =
+        fs::read_to_string(hint_inputs_path(keys, sud_path, cell)).expect("Unable to read file");
+    let hint_inputs: HintInputs = serde_json::from_str(&toj).unwrap();
+    let toj =
+        fs::read_to_string(hint_proof_path(keys, sud_path, cell)).expect("Unable to read file");
+    let proof = serde_json::from_str::<ProofHexed>(&toj)
+        .unwrap()
+        .proof(read_encoding(keys));
+
+    let Some(solution_hash) = pub_inputs.solution_hash else {
+        return false;
+    };
+    let inputs = [
+        dec_to_field(&pub_inputs.pub1),
+        dec_to_field(&solution_hash),
+        E::ScalarField::from(cell.0 as u64),
+        E::ScalarField::from(cell.1 as u64),
+        E::ScalarField::from(hint_inputs.value),
+    ];
+    verify_proof::<E>(&read_vk(&hint_keys(keys)), &inputs, &proof)
+}
+
+/// Progress keys live with the sudoku keys whose puzzle hash they share
+fn progress_keys(keys: &str) -> String {
+    format!("{}/progress", keys)
+}
+
+fn progress_init<const N: usize, E>(keys: &str)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = load_rules::<N>(&keys_rules_path(keys));
+    let (pk, vk) = progress::setup::<N, E>(&poseidon_config, &rules, read_commitment(keys));
+    write_keys(&progress_keys(keys), &pk, &vk, read_encoding(keys));
+}
+
+fn progress_prove<const N: usize, E>(keys: &str, sud_path: &str) -> u64
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+    E::ScalarField: Absorb,
+{
+    let pk = read_pk(&progress_keys(keys));
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = read_sudoku_rules::<N>(keys, sud_path);
+    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(sud_path)));
+    let parity = Parity(read_parity::<N>(&sudoku_puzzle_path(sud_path)));
+    let commitment = read_commitment(keys);
+    let salt = read_salt(sud_path);
+    let progress = Progress {
+        hash: Some(commitment.commit(&poseidon_config, &puzzle, &parity, &rules, salt)),
+        poseidon_config,
+        puzzle: Some(puzzle),
+        parity: Some(parity),
+        rules,
+        commitment,
+        salt: Some(salt),
+        grid: Some(read_grid::<N>(&sudoku_progress_path(sud_path))),
+    };
+
+    let proof = mk_proof(&pk, &progress);
+    fs::write(
+        progress_proof_path(keys, sud_path),
+        serde_json::to_string_pretty(&ProofHexed::new(&proof, read_encoding(keys))).unwrap(),
+    )
+    .unwrap();
+    let progress_inputs = ProgressInputs {
+        pub1: field_to_dec(&progress.hash.unwrap()),
+        filled: progress.filled(),
+    };
+    fs::write(
+        progress_inputs_path(keys, sud_path),
+        serde_json::to_string_pretty(&progress_inputs).unwrap(),
     )
     .unwrap();
 
-    // Propose problem (here with solution)
-    // Solver solves it (not here) and creates proof
-    let puzzle = Puzzle(read_grid::<N>(&sudoku_puzzle_path(&sud_path)));
-    let solution = Solution(read_grid::<N>(&sudoku_solution_path(&sud_path)));
-    let sudoku = mk_sudoku::<N, E>(&poseidon_config, &PuzSol { puzzle, solution });
+    let vk = read_vk(&progress_keys(keys));
+    check_proof::<E>(&vk, &progress.public_inputs(), &proof);
+    progress.filled()
+}
+
+/// Whether the progress proof holds for the puzzle hash and count in its datum
+fn progress_verify<E>(keys: &str, sud_path: &str) -> bool
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    let toj =
+        fs::read_to_string(progress_inputs_path(keys, sud_path)).expect("Unable to read file");
+    let progress_inputs: ProgressInputs = serde_json::from_str(&toj).unwrap();
+    let toj = fs::read_to_string(progress_proof_path(keys, sud_path)).expect("Unable to read file");
+    let proof = serde_json::from_str::<ProofHexed>(&toj)
+        .unwrap()
+        .proof(read_encoding(keys));
+
+    let inputs = [
+        dec_to_field(&progress_inputs.pub1),
+        E::ScalarField::from(progress_inputs.filled),
+    ];
+    verify_proof::<E>(&read_vk(&progress_keys(keys)), &inputs, &proof)
+}
+
+fn write_keys<E>(name: &str, pk: &ProvingKey<E>, vk: &VerifyingKey<E>, encoding: Encoding)
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    // write pk
+    let mut v_pk = Vec::new();
+    pk.serialize_compressed(&mut v_pk).unwrap();
+    fs::write(pk_path(name), v_pk).unwrap();
 
-    let proof = mk_proof(&pk, &sudoku);
-    let proof_hexed = ProofHexed::from(proof.clone());
+    // write vk
+    let mut v_vk = Vec::new();
+    vk.serialize_compressed(&mut v_vk).unwrap();
+    fs::write(vk_path(name), v_vk).unwrap();
+
+    // write vk_hexed
+    let vk_hexed = VkHexed::new(vk, encoding);
     fs::write(
-        proof_hexed_path(keys, sud_path),
-        serde_json::to_string_pretty(&proof_hexed).unwrap(),
+        vk_hexed_path(name),
+        serde_json::to_string_pretty(&vk_hexed).unwrap(),
     )
     .unwrap();
+}
 
-    let vk_bin: Vec<u8> = fs::read(&vk_path(keys)).unwrap();
-    let vk = VerifyingKey::<E>::deserialize_with_mode(
-        &vk_bin[..],
+fn read_pk<E: Pairing>(name: &str) -> ProvingKey<E> {
+    let pk_bin: Vec<u8> = fs::read(pk_path(name)).unwrap();
+    ProvingKey::<E>::deserialize_with_mode(
+        &pk_bin[..],
         ark_serialize::Compress::Yes,
         ark_serialize::Validate::Yes,
     )
-    .unwrap();
+    .unwrap()
+}
 
-    check_proof::<E>(&vk, &sudoku.hash.unwrap(), &proof);
-    Ok(())
+fn read_vk<E: Pairing>(name: &str) -> VerifyingKey<E> {
+    let vk_bin: Vec<u8> = fs::read(vk_path(name)).unwrap();
+    VerifyingKey::<E>::deserialize_with_mode(
+        &vk_bin[..],
+        ark_serialize::Compress::Yes,
+        ark_serialize::Validate::Yes,
+    )
+    .unwrap()
 }
 
 // TODO : Move these somewhere
 
 fn out_root() -> String {
-    format!("./out")
+    "./out".to_string()
 }
 fn keys_path(name: &str, file: &str) -> String {
     format!("{}/keys/{}/{}", out_root(), name, file)
@@ -189,6 +1574,30 @@ fn vk_path(name: &str) -> String {
 fn vk_hexed_path(name: &str) -> String {
     keys_path(name, "params.json")
 }
+fn keys_rules_path(name: &str) -> String {
+    keys_path(name, "rules.json")
+}
+fn keys_commitment_path(name: &str) -> String {
+    keys_path(name, "commitment.json")
+}
+fn keys_curve_path(name: &str) -> String {
+    keys_path(name, "curve.json")
+}
+fn keys_manifest_path(name: &str) -> String {
+    keys_path(name, "manifest.json")
+}
+fn keys_encoding_path(name: &str) -> String {
+    keys_path(name, "encoding.json")
+}
+fn solidity_verifier_path(name: &str) -> String {
+    keys_path(name, "Verifier.sol")
+}
+fn aiken_module_path(name: &str) -> String {
+    keys_path(name, "verifying_key.ak")
+}
+fn snarkjs_vk_path(name: &str) -> String {
+    keys_path(name, "verification_key.json")
+}
 fn sudoku_path(name: &str, file: &str) -> String {
     format!("{}/sudokus/{}/{}", out_root(), name, file)
 }
@@ -198,6 +1607,24 @@ fn sudoku_puzzle_path(name: &str) -> String {
 fn sudoku_solution_path(name: &str) -> String {
     sudoku_path(name, "solution.ssv")
 }
+fn sudoku_progress_path(name: &str) -> String {
+    sudoku_path(name, "progress.ssv")
+}
+fn samurai_puzzle_path(name: &str) -> String {
+    sudoku_path(name, "puzzle.samurai")
+}
+fn samurai_solution_path(name: &str) -> String {
+    sudoku_path(name, "solution.samurai")
+}
+fn sudoku_salt_path(name: &str) -> String {
+    sudoku_path(name, "salt.json")
+}
+fn sudoku_rules_path(name: &str) -> String {
+    sudoku_path(name, "rules.json")
+}
+fn sudoku_certificate_path(name: &str) -> String {
+    sudoku_path(name, "certificate.json")
+}
 fn results_path(keys: &str, sudoku: &str, file: &str) -> String {
     format!("{}/results/{}_{}/{}", out_root(), keys, sudoku, file)
 }
@@ -207,6 +1634,45 @@ fn proof_hexed_path(keys: &str, sudoku: &str) -> String {
 fn pub_inputs_path(keys: &str, sudoku: &str) -> String {
     results_path(keys, sudoku, "datum.json")
 }
+fn plutus_datum_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "datum.plutus.json")
+}
+fn plutus_datum_cbor_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "datum.cbor")
+}
+fn plutus_redeemer_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "redeemer.plutus.json")
+}
+fn plutus_redeemer_cbor_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "redeemer.cbor")
+}
+fn calldata_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "calldata.txt")
+}
+fn snarkjs_proof_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "proof.json")
+}
+fn snarkjs_public_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "public.json")
+}
+fn solution_salt_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "solution_salt.json")
+}
+fn hint_proof_path(keys: &str, sudoku: &str, (row, col): Cell) -> String {
+    results_path(keys, sudoku, &format!("hint_{}_{}.json", row, col))
+}
+fn hint_inputs_path(keys: &str, sudoku: &str, (row, col): Cell) -> String {
+    results_path(keys, sudoku, &format!("hint_{}_{}_datum.json", row, col))
+}
+fn progress_proof_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "progress.json")
+}
+fn progress_inputs_path(keys: &str, sudoku: &str) -> String {
+    results_path(keys, sudoku, "progress_datum.json")
+}
+fn row_opening_path(keys: &str, sudoku: &str, row: usize) -> String {
+    results_path(keys, sudoku, &format!("row_{}.json", row))
+}
 
 fn write_poseidon_config<F: PrimeField + Absorb>(name: &str, n: usize) {
     let poseidon_config = mk_poseidon_config::<F>(n);
@@ -221,3 +1687,282 @@ fn read_poseidon_config<F: PrimeField + Absorb>(name: &str) -> PoseidonConfig<F>
     let pc: PoseidonConfig<F> = froj.into();
     pc
 }
+
+/// Rules of a json file for an N x N grid, panicking with the reason they are invalid
+fn load_rules<const N: usize>(fp: &str) -> Vec<ExtraRule> {
+    read_rules::<N>(fp).unwrap_or_else(|e| panic!("invalid rules {}: {}", fp, e))
+}
+
+/// Rules of the sudoku, which must be those the keys were generated for
+fn read_sudoku_rules<const N: usize>(keys: &str, sud_path: &str) -> Vec<ExtraRule> {
+    let rules = load_rules::<N>(&sudoku_rules_path(sud_path));
+    if rules != load_rules::<N>(&keys_rules_path(keys)) {
+        panic!("rules of {} do not match those of keys {}", sud_path, keys)
+    }
+    rules
+}
+
+/// How the keys commit to puzzles. A missing file means flat, as for keys made before Merkle trees.
+fn read_commitment(keys: &str) -> Commitment {
+    match fs::read_to_string(keys_commitment_path(keys)) {
+        Ok(toj) => serde_json::from_str(&toj).unwrap(),
+        Err(_) => Commitment::Flat,
+    }
+}
+
+fn write_curve(keys: &str, curve: Curve) {
+    fs::write(
+        keys_curve_path(keys),
+        serde_json::to_string_pretty(&curve).unwrap(),
+    )
+    .unwrap();
+}
+
+/// SHA-256 of the constraint matrices of the circuit and their sizes, synthesized as setup does,
+/// so keys can be matched to the circuit this build would set them up for
+fn circuit_fingerprint<F: PrimeField, C: ConstraintSynthesizer<F>>(circuit: C) -> String {
+    let cs = ConstraintSystem::<F>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    let mut hasher = Sha256::new();
+    for count in [
+        matrices.num_instance_variables,
+        matrices.num_witness_variables,
+        matrices.num_constraints,
+    ] {
+        hasher.update((count as u64).to_le_bytes());
+    }
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        for row in matrix {
+            hasher.update((row.len() as u64).to_le_bytes());
+            for (coeff, var) in row {
+                hasher.update(coeff.into_bigint().to_bytes_le());
+                hasher.update((*var as u64).to_le_bytes());
+            }
+        }
+    }
+    hex_vec(&hasher.finalize())
+}
+
+/// Fingerprint of the `circuit` this build sets up with the settings of the keys
+fn keys_circuit_fingerprint<E>(keys: &str, circuit: &str) -> String
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let poseidon_config = read_poseidon_config::<E::ScalarField>(keys);
+    let rules = load_rules::<DIM>(&keys_rules_path(keys));
+    let commitment = read_commitment(keys);
+    match circuit {
+        "sudoku" => circuit_fingerprint(blank_sudoku::<DIM, E::ScalarField>(
+            &poseidon_config,
+            &rules,
+            commitment,
+        )),
+        "samurai" => circuit_fingerprint(samurai::blank_samurai(&poseidon_config)),
+        "hint" => circuit_fingerprint(hint::blank_hint::<DIM, E::ScalarField>(
+            &poseidon_config,
+            &rules,
+            commitment,
+        )),
+        "progress" => circuit_fingerprint(progress::blank_progress::<DIM, E::ScalarField>(
+            &poseidon_config,
+            &rules,
+            commitment,
+        )),
+        _ => panic!("no circuit {}", circuit),
+    }
+}
+
+/// Where the keys of `circuit` are. Hint and progress keys sit inside the sudoku keys,
+/// whose curve, poseidon config, rules and commitment they share.
+fn circuit_keys(keys: &str, circuit: &str) -> String {
+    match circuit {
+        "hint" => hint_keys(keys),
+        "progress" => progress_keys(keys),
+        _ => keys.to_string(),
+    }
+}
+
+/// What of the keys a command relies on, and so how much of the manifest it checks
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Needs {
+    /// The verifying key and settings, as checking a proof or datum does
+    Verifying,
+    /// Those, and the circuit the keys were set up for, to hash puzzles as it does or set up beside it
+    Circuit,
+    /// Those, and the proving key
+    Proving,
+}
+
+/// Files of the manifest that a command with these `needs` reads
+fn needed_files(needs: Needs) -> impl Iterator<Item = &'static str> {
+    MANIFEST_FILES
+        .into_iter()
+        .filter(move |file| needs == Needs::Proving || *file != "pk.bin")
+}
+
+/// SHA-256 of each of `files` present in `dir`, by name
+fn hash_files(dir: &str, files: impl Iterator<Item = &'static str>) -> BTreeMap<String, String> {
+    files
+        .filter_map(|file| {
+            let bytes = fs::read(keys_path(dir, file)).ok()?;
+            Some((file.to_string(), hex_vec(&Sha256::digest(bytes))))
+        })
+        .collect()
+}
+
+/// Manifest of the keys of `circuit` as they are now, for a grid of `size`
+fn mk_manifest(keys: &str, circuit: &str, size: usize) -> Manifest {
+    let dir = circuit_keys(keys, circuit);
+    let files = hash_files(&dir, needed_files(Needs::Proving));
+    Manifest {
+        circuit: circuit.to_string(),
+        size,
+        curve: read_curve(keys),
+        commitment: read_commitment(keys),
+        rules: load_rules::<DIM>(&keys_rules_path(keys)),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        constraints: on_curve!(read_curve(keys), E => keys_circuit_fingerprint::<E>(keys, circuit)),
+        files,
+    }
+}
+
+fn write_manifest(keys: &str, circuit: &str, size: usize) {
+    fs::write(
+        keys_manifest_path(&circuit_keys(keys, circuit)),
+        serde_json::to_string_pretty(&mk_manifest(keys, circuit, size)).unwrap(),
+    )
+    .unwrap();
+}
+
+fn read_manifest(keys: &str) -> Manifest {
+    let toj = fs::read_to_string(keys_manifest_path(keys)).unwrap_or_else(|_| {
+        panic!(
+            "keys {} have no manifest.json, so may not fit this build. Run init again",
+            keys
+        )
+    });
+    serde_json::from_str(&toj).unwrap()
+}
+
+/// Panic unless the keys of `circuit` were generated for this build's circuit of `size`,
+/// and the files a command with these `needs` reads are those fingerprinted when they were.
+/// Only commands that need the circuit synthesize it to compare, and only provers hash pk.bin.
+fn check_manifest(keys: &str, circuit: &str, size: usize, needs: Needs) {
+    let dir = circuit_keys(keys, circuit);
+    let manifest = read_manifest(&dir);
+    if (manifest.circuit.as_str(), manifest.size) != (circuit, size) {
+        panic!(
+            "keys {} are for a {} of size {}, not a {} of size {}",
+            dir, manifest.circuit, manifest.size, circuit, size
+        )
+    }
+    let version = env!("CARGO_PKG_VERSION");
+    if manifest.version != version {
+        panic!(
+            "keys {} were generated by version {}, not {}",
+            dir, manifest.version, version
+        )
+    }
+    if needs != Needs::Verifying
+        && manifest.constraints
+            != on_curve!(read_curve(keys), E => keys_circuit_fingerprint::<E>(keys, circuit))
+    {
+        panic!(
+            "keys {} were set up for another circuit than this build's. Run init again",
+            dir
+        )
+    }
+    let current = hash_files(&dir, needed_files(needs));
+    let changed = needed_files(needs)
+        .filter(|file| manifest.files.get(*file) != current.get(*file))
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        panic!(
+            "keys {} do not match manifest.json in {}",
+            dir,
+            changed.join(", ")
+        )
+    }
+}
+
+/// Check the manifest of keys that may be a sudoku's or a samurai's, against whichever it records,
+/// for a command that only reads the verifying key and settings
+fn check_any_manifest(keys: &str) {
+    match read_manifest(keys).circuit.as_str() {
+        "samurai" => check_manifest(keys, "samurai", 9, Needs::Verifying),
+        _ => check_manifest(keys, "sudoku", DIM, Needs::Verifying),
+    }
+}
+
+fn write_encoding(keys: &str, encoding: Encoding) {
+    fs::write(
+        keys_encoding_path(keys),
+        serde_json::to_string_pretty(&encoding).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Encoding of the points of the keys and their proofs. A missing file means compressed, as before the choice.
+fn read_encoding(keys: &str) -> Encoding {
+    match fs::read_to_string(keys_encoding_path(keys)) {
+        Ok(toj) => serde_json::from_str(&toj).unwrap(),
+        Err(_) => Encoding::Compressed,
+    }
+}
+
+/// Pairing of the keys. A missing file means BLS12-381, as for keys made before BN254.
+fn read_curve(keys: &str) -> Curve {
+    match fs::read_to_string(keys_curve_path(keys)) {
+        Ok(toj) => serde_json::from_str(&toj).unwrap(),
+        Err(_) => Curve::Bls12381,
+    }
+}
+
+fn write_salt<F: PrimeField>(sud_path: &str, salt: F) {
+    let salt = Salt {
+        salt: format!("{}", salt),
+    };
+    fs::write(
+        sudoku_salt_path(sud_path),
+        serde_json::to_string_pretty(&salt).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Salt of the puzzle commitment. A missing file means unsalted.
+fn read_salt<F: PrimeField>(sud_path: &str) -> F {
+    match fs::read_to_string(sudoku_salt_path(sud_path)) {
+        Ok(toj) => {
+            let salt: Salt = serde_json::from_str(&toj).unwrap();
+            dec_to_field(&salt.salt)
+        }
+        Err(_) => F::zero(),
+    }
+}
+
+fn write_solution_salt<F: PrimeField>(keys: &str, sud_path: &str, salt: F) {
+    let salt = Salt {
+        salt: format!("{}", salt),
+    };
+    fs::write(
+        solution_salt_path(keys, sud_path),
+        serde_json::to_string_pretty(&salt).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Salt of the solution commitment. A missing file means unsalted.
+fn read_solution_salt<F: PrimeField>(keys: &str, sud_path: &str) -> F {
+    match fs::read_to_string(solution_salt_path(keys, sud_path)) {
+        Ok(toj) => {
+            let salt: Salt = serde_json::from_str(&toj).unwrap();
+            dec_to_field(&salt.salt)
+        }
+        Err(_) => F::zero(),
+    }
+}
@@ -0,0 +1,245 @@
+//! Poseidon Merkle commitment to a puzzle, one leaf per row,
+//! so a setter can later reveal single rows with a membership proof.
+//! Each row is blinded by its own salt, derived from the puzzle salt,
+//! so revealing a row says nothing about the others.
+use ark_crypto_primitives::crh::poseidon::constraints::{
+    CRHGadget, CRHParametersVar, TwoToOneCRHGadget,
+};
+use ark_crypto_primitives::crh::poseidon::{TwoToOneCRH, CRH};
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::crh::{TwoToOneCRHScheme, TwoToOneCRHSchemeGadget};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::SynthesisError;
+
+use crate::pack::{cell_bits, pack, pack_var};
+use crate::parity::{Parity, EVEN, PARITY_BITS};
+use crate::{GridVar, Puzzle};
+
+/// A revealed row with its path to the root
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowOpening<F: PrimeField> {
+    pub index: usize,
+    pub cells: Vec<u8>,
+    pub marks: Vec<u8>,
+    /// Salt of this row alone
+    pub salt: F,
+    /// Siblings from the leaf up
+    pub path: Vec<F>,
+}
+
+/// Salt of row `i`, from the puzzle salt
+pub fn row_salt<F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    salt: F,
+    i: usize,
+) -> F {
+    CRH::<F>::evaluate(poseidon_config, [salt, F::from(i as u64)]).unwrap()
+}
+
+/// Leaf of a row: its packed cells and parity marks, then its salt
+pub fn leaf<F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    cells: &[u8],
+    marks: &[u8],
+    salt: F,
+) -> F {
+    let n = cells.len();
+    let cells = cells.iter().map(|&x| (x, cell_bits(n)));
+    let marks = marks.iter().map(|&x| (x, PARITY_BITS));
+    let mut x = pack::<F>(&cells.chain(marks).collect::<Vec<_>>());
+    x.push(salt);
+    CRH::<F>::evaluate(poseidon_config, x).unwrap()
+}
+
+fn leaves<const N: usize, F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    salt: F,
+) -> Vec<F> {
+    (0..N)
+        .map(|i| {
+            let salt = row_salt(poseidon_config, salt, i);
+            leaf(poseidon_config, &puzzle.0[i], &parity.0[i], salt)
+        })
+        .collect()
+}
+
+/// Levels of the tree from the leaves up, padded with zero leaves to a power of two
+fn levels<F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    leaves: Vec<F>,
+) -> Vec<Vec<F>> {
+    let mut level = leaves;
+    level.resize(level.len().next_power_of_two(), F::zero());
+    let mut levels = vec![level];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| TwoToOneCRH::<F>::compress(poseidon_config, pair[0], pair[1]).unwrap())
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
+
+/// Root of the tree over the rows of the puzzle
+pub fn merkle_root<const N: usize, F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    salt: F,
+) -> F {
+    let levels = levels(
+        poseidon_config,
+        leaves(poseidon_config, puzzle, parity, salt),
+    );
+    levels.last().unwrap()[0]
+}
+
+/// Reveal row `index` of the puzzle, if it has one
+pub fn open_row<const N: usize, F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    salt: F,
+    index: usize,
+) -> Result<RowOpening<F>, String> {
+    if index >= N {
+        return Err(format!("row {} is not in 0..{}", index, N));
+    }
+    let levels = levels(
+        poseidon_config,
+        leaves(poseidon_config, puzzle, parity, salt),
+    );
+    let path = levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(depth, level)| level[(index >> depth) ^ 1])
+        .collect();
+    Ok(RowOpening {
+        index,
+        cells: puzzle.0[index].to_vec(),
+        marks: parity.0[index].to_vec(),
+        salt: row_salt(poseidon_config, salt, index),
+        path,
+    })
+}
+
+/// Whether the opening is of a row of the puzzle with this root
+pub fn verify_row<F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    root: F,
+    opening: &RowOpening<F>,
+) -> bool {
+    let n = opening.cells.len();
+    if opening.marks.len() != n
+        || opening.index >= n
+        || opening.path.len() != n.next_power_of_two().trailing_zeros() as usize
+        || opening.cells.iter().any(|&x| x as usize > n)
+        || opening.marks.iter().any(|&x| x > EVEN)
+    {
+        return false;
+    }
+    let leaf = leaf(
+        poseidon_config,
+        &opening.cells,
+        &opening.marks,
+        opening.salt,
+    );
+    let node = opening
+        .path
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (depth, &sibling)| {
+            match (opening.index >> depth) & 1 {
+                0 => TwoToOneCRH::<F>::compress(poseidon_config, node, sibling),
+                _ => TwoToOneCRH::<F>::compress(poseidon_config, sibling, node),
+            }
+            .unwrap()
+        });
+    node == root
+}
+
+/// Root of the tree in the circuit, computed as by `merkle_root`
+pub fn merkle_root_var<const N: usize, F: PrimeField + Absorb>(
+    params: &CRHParametersVar<F>,
+    puzzle: &GridVar<N, F>,
+    parity: &GridVar<N, F>,
+    salt: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut level = vec![];
+    for (i, (cells, marks)) in puzzle.iter().zip(parity).enumerate() {
+        let row_salt =
+            CRHGadget::<F>::evaluate(params, &[salt.clone(), FpVar::constant(F::from(i as u64))])?;
+        let cells = cells.iter().map(|x| (x, cell_bits(N)));
+        let marks = marks.iter().map(|x| (x, PARITY_BITS));
+        let mut x = pack_var(&cells.chain(marks).collect::<Vec<(&UInt8<F>, usize)>>())?;
+        x.push(row_salt);
+        level.push(CRHGadget::<F>::evaluate(params, &x)?);
+    }
+    level.resize(N.next_power_of_two(), FpVar::zero());
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| TwoToOneCRHGadget::<F>::compress(params, &pair[0], &pair[1]))
+            .collect::<Result<_, _>>()?;
+    }
+    Ok(level.remove(0))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+    use ark_r1cs_std::{fields::fp::FpVar, prelude::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::merkle::{merkle_root, merkle_root_var, open_row, verify_row};
+    use crate::parity::{Parity, ParityVar, ODD};
+    use crate::poseidon::mk_poseidon_config;
+    use crate::{mk_salt, Puzzle, PuzzleVar};
+
+    #[test]
+    fn test_merkle() {
+        let poseidon_config = mk_poseidon_config::<Fp>(9);
+        let puzzle = Puzzle::<9>(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i * 3 + i / 3 + j) % 9 + 1) as u8 * ((i + j) % 2) as u8)
+        }));
+        let mut parity = Parity::default();
+        parity.0[4][4] = ODD;
+        let salt = mk_salt::<Fp>();
+        let root = merkle_root(&poseidon_config, &puzzle, &parity, salt);
+
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        let params = CRHParametersVar::new_witness(cs.clone(), || Ok(&poseidon_config)).unwrap();
+        let puzzle_var = PuzzleVar::<9, Fp>::new_witness(cs.clone(), || Ok(puzzle.0)).unwrap();
+        let parity_var = ParityVar::<9, Fp>::new_witness(cs.clone(), || Ok(parity.0)).unwrap();
+        let salt_var = FpVar::new_witness(cs.clone(), || Ok(salt)).unwrap();
+        let root_var = merkle_root_var(&params, &puzzle_var.0, &parity_var.0, &salt_var).unwrap();
+        assert_eq!(root_var.value().unwrap(), root);
+
+        for i in 0..9 {
+            let opening = open_row(&poseidon_config, &puzzle, &parity, salt, i).unwrap();
+            assert!(verify_row(&poseidon_config, root, &opening));
+
+            let mut wrong = opening.clone();
+            wrong.cells[0] ^= 1;
+            assert!(!verify_row(&poseidon_config, root, &wrong));
+            // A cell too wide for the grid must not verify as its low bits
+            let mut wrong = opening.clone();
+            wrong.cells[0] += 16;
+            assert!(!verify_row(&poseidon_config, root, &wrong));
+            let mut wrong = opening;
+            wrong.index ^= 1;
+            assert!(!verify_row(&poseidon_config, root, &wrong));
+        }
+        assert!(open_row(&poseidon_config, &puzzle, &parity, salt, 9).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Packing of small values into field elements before hashing.
+//! Each value takes a fixed number of bits, little endian, and the bits are cut
+//! into chunks of `CHUNK_BITS`. The native and gadget versions agree exactly.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    fields::fp::FpVar,
+    prelude::{Boolean, EqGadget},
+    uint8::UInt8,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::SynthesisError;
+
+/// Bits per field element, 31 bytes so any chunk is below the modulus
+pub const CHUNK_BITS: usize = 248;
+
+/// Bits needed for a cell of an `n` by `n` grid, ie. for values `0..=n`
+pub fn cell_bits(n: usize) -> usize {
+    (usize::BITS - n.leading_zeros()) as usize
+}
+
+/// Pack each value into field elements, panicking if one is wider than its width
+pub fn pack<F: PrimeField>(values: &[(u8, usize)]) -> Vec<F> {
+    for &(x, width) in values {
+        assert!(
+            (x as u32) >> width == 0,
+            "value {} does not fit in {} bits",
+            x,
+            width
+        );
+    }
+    let bits = values
+        .iter()
+        .flat_map(|&(x, width)| (0..width).map(move |i| (x >> i) & 1 == 1))
+        .collect::<Vec<bool>>();
+    bits.chunks(CHUNK_BITS)
+        .map(|chunk| F::from_bigint(F::BigInt::from_bits_le(chunk)).unwrap())
+        .collect()
+}
+
+/// Pack each value into field elements as `pack` does,
+/// enforcing the bits above its width are zero.
+pub fn pack_var<F: PrimeField>(
+    values: &[(&UInt8<F>, usize)],
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut bits = vec![];
+    for &(x, width) in values {
+        let x_bits = x.to_bits_le()?;
+        for bit in &x_bits[width..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        bits.extend_from_slice(&x_bits[..width]);
+    }
+    bits.chunks(CHUNK_BITS)
+        .map(Boolean::le_bits_to_fp_var)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_r1cs_std::{prelude::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::pack::{cell_bits, pack, pack_var, CHUNK_BITS};
+
+    #[test]
+    fn test_pack() {
+        assert_eq!(cell_bits(2), 2);
+        assert_eq!(cell_bits(9), 4);
+        assert_eq!(cell_bits(25), 5);
+
+        let values = (0..100).map(|i| (i % 26, 5)).collect::<Vec<(u8, usize)>>();
+        let native = pack::<Fp>(&values);
+        assert_eq!(native.len(), (100 * 5_usize).div_ceil(CHUNK_BITS));
+
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        let vars = values
+            .iter()
+            .map(|&(x, _)| UInt8::new_witness(cs.clone(), || Ok(x)).unwrap())
+            .collect::<Vec<_>>();
+        let packed = pack_var(
+            &vars
+                .iter()
+                .zip(values.iter().map(|v| v.1))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+        .iter()
+        .map(|x| x.value().unwrap())
+        .collect::<Vec<Fp>>();
+        assert_eq!(packed, native);
+        assert!(cs.is_satisfied().unwrap());
+
+        // A value too wide for its bits is rejected
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        let x = UInt8::new_witness(cs.clone(), || Ok(16)).unwrap();
+        pack_var(&[(&x, 4)]).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        assert!(std::panic::catch_unwind(|| pack::<Fp>(&[(16, 4)])).is_err());
+    }
+}
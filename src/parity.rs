@@ -0,0 +1,122 @@
+//! Cells marked as needing an even or odd digit, in place of a given.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    prelude::{Boolean, EqGadget},
+    uint8::UInt8,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::cmp::CmpGadget;
+use crate::{read_cells, Grid, GridVar};
+
+/// No constraint on the cell
+pub const ANY: u8 = 0;
+/// The cell is odd
+pub const ODD: u8 = 1;
+/// The cell is even
+pub const EVEN: u8 = 2;
+
+/// Bits a mark is packed into for hashing
+pub const PARITY_BITS: usize = 2;
+
+/// Parity mask of a puzzle, one of `ANY`, `ODD` or `EVEN` per cell
+#[derive(Clone, Copy, Debug)]
+pub struct Parity<const N: usize>(pub Grid<N>);
+
+pub struct ParityVar<const N: usize, F: PrimeField>(pub GridVar<N, F>);
+
+impl<const N: usize> Parity<N> {
+    pub fn is_satisfied(&self, grid: &Grid<N>) -> bool {
+        self.0
+            .iter()
+            .flatten()
+            .zip(grid.iter().flatten())
+            .all(|(&m, &x)| keeps_mark(m, x))
+    }
+}
+
+/// Whether the digit `x` agrees with the mark `m`
+pub fn keeps_mark(m: u8, x: u8) -> bool {
+    match m {
+        ANY => true,
+        ODD => !x.is_multiple_of(2),
+        EVEN => x.is_multiple_of(2),
+        _ => false,
+    }
+}
+
+impl<const N: usize> Default for Parity<N> {
+    fn default() -> Self {
+        Parity([[ANY; N]; N])
+    }
+}
+
+/// Enforce the parity mask is well formed, and the solution agrees with it
+/// on the least significant bit of each cell.
+pub fn enforce_parity<const N: usize, F: PrimeField>(
+    parity: &GridVar<N, F>,
+    solution: &GridVar<N, F>,
+) -> Result<(), SynthesisError> {
+    for (m_row, s_row) in parity.iter().zip(solution) {
+        for (m, s) in m_row.iter().zip(s_row) {
+            m.is_leq(&UInt8::constant(EVEN))?
+                .enforce_equal(&Boolean::TRUE)?;
+            breaks_parity(m, s)?.enforce_equal(&Boolean::FALSE)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the cell is odd where marked even, or even where marked odd
+pub fn breaks_parity<F: PrimeField>(
+    m: &UInt8<F>,
+    s: &UInt8<F>,
+) -> Result<Boolean<F>, SynthesisError> {
+    let is_odd = s.to_bits_le()?[0].clone();
+    let wants_odd = m.is_eq(&UInt8::constant(ODD))?;
+    let wants_even = m.is_eq(&UInt8::constant(EVEN))?;
+    wants_odd.and(&is_odd.not())?.or(&wants_even.and(&is_odd)?)
+}
+
+/// Read the parity mask from a puzzle file, where `o` and `e` mark odd and even cells
+pub fn read_parity<const N: usize>(fp: &str) -> Grid<N> {
+    read_cells(fp, |x| match x {
+        "o" => ODD,
+        "e" => EVEN,
+        _ => ANY,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_r1cs_std::prelude::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::parity::{enforce_parity, Parity, ParityVar, ANY, EVEN, ODD};
+    use crate::{Grid, SolutionVar};
+
+    const GRID: Grid<4> = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+    fn check(parity: Parity<4>, expected: bool) {
+        assert_eq!(parity.is_satisfied(&GRID), expected);
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        let parity = ParityVar::<4, Fp>::new_witness(cs.clone(), || Ok(parity.0)).unwrap();
+        let solution = SolutionVar::<4, Fp>::new_witness(cs.clone(), || Ok(GRID)).unwrap();
+        enforce_parity(&parity.0, &solution.0).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parity() {
+        check(Parity::default(), true);
+        let mut parity = Parity::default();
+        parity.0[0] = [ODD, EVEN, ODD, ANY];
+        check(parity, true);
+        parity.0[1][1] = ODD;
+        check(parity, false);
+        parity.0[1][1] = 3;
+        check(parity, false);
+    }
+}
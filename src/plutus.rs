@@ -0,0 +1,214 @@
+//! Plutus Data, the form datums and redeemers take on chain, written as `cardano-cli`'s
+//! detailed schema JSON and as CBOR.
+//! Field elements are integers, and group elements bytestrings of their compressed encoding.
+use ark_ec::pairing::Pairing;
+use ark_groth16::Proof;
+use ark_serialize::CanonicalSerialize;
+use num_bigint::BigUint;
+
+use crate::ss_serde::{hex_vec, PubInputs};
+
+/// The parts of Plutus Data used here. Integers are never negative.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlutusData {
+    Constr(u64, Vec<PlutusData>),
+    Int(BigUint),
+    Bytes(Vec<u8>),
+    List(Vec<PlutusData>),
+}
+
+/// Bytestrings longer than this are split into chunks, as the ledger requires
+const CHUNK: usize = 64;
+
+impl PlutusData {
+    /// `Some x` as Plutus encodes an option, and `None` for `None`
+    fn option(x: Option<PlutusData>) -> PlutusData {
+        match x {
+            Some(x) => PlutusData::Constr(0, vec![x]),
+            None => PlutusData::Constr(1, vec![]),
+        }
+    }
+
+    /// Detailed schema JSON, as read by `cardano-cli`
+    pub fn to_json(&self) -> String {
+        let list = |xs: &[PlutusData]| {
+            xs.iter()
+                .map(|x| x.to_json())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            PlutusData::Constr(i, fields) => {
+                format!("{{\"constructor\": {}, \"fields\": [{}]}}", i, list(fields))
+            }
+            PlutusData::Int(x) => format!("{{\"int\": {}}}", x),
+            PlutusData::Bytes(bytes) => format!("{{\"bytes\": \"{}\"}}", hex_vec(bytes)),
+            PlutusData::List(xs) => format!("{{\"list\": [{}]}}", list(xs)),
+        }
+    }
+
+    /// CBOR, as the ledger encodes it
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write_cbor(&mut out);
+        out
+    }
+
+    fn write_cbor(&self, out: &mut Vec<u8>) {
+        match self {
+            PlutusData::Constr(i, fields) => {
+                match i {
+                    0..=6 => head(out, 6, 121 + i),
+                    7..=127 => head(out, 6, 1280 + i - 7),
+                    _ => {
+                        // General form, the index then the fields
+                        head(out, 6, 102);
+                        head(out, 4, 2);
+                        head(out, 0, *i);
+                    }
+                }
+                write_list(out, fields);
+            }
+            PlutusData::Int(x) => match u64::try_from(x) {
+                Ok(x) => head(out, 0, x),
+                Err(_) => {
+                    // Positive bignum
+                    head(out, 6, 2);
+                    write_bytes(out, &x.to_bytes_be());
+                }
+            },
+            PlutusData::Bytes(bytes) => write_bytes(out, bytes),
+            PlutusData::List(xs) => write_list(out, xs),
+        }
+    }
+}
+
+/// Head of a CBOR item of the major type, with its argument in the fewest bytes
+fn head(out: &mut Vec<u8>, major: u8, n: u64) {
+    let major = major << 5;
+    match n {
+        0..=23 => out.push(major | n as u8),
+        24..=0xff => out.extend([major | 24, n as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((n as u16).to_be_bytes())
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((n as u32).to_be_bytes())
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(n.to_be_bytes())
+        }
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() <= CHUNK {
+        head(out, 2, bytes.len() as u64);
+        out.extend(bytes);
+        return;
+    }
+    // Indefinite length, in chunks
+    out.push(0x5f);
+    for chunk in bytes.chunks(CHUNK) {
+        head(out, 2, chunk.len() as u64);
+        out.extend(chunk);
+    }
+    out.push(0xff);
+}
+
+/// Lists are indefinite length unless empty, as the ledger writes them
+fn write_list(out: &mut Vec<u8>, xs: &[PlutusData]) {
+    if xs.is_empty() {
+        head(out, 4, 0);
+        return;
+    }
+    out.push(0x9f);
+    for x in xs {
+        x.write_cbor(out);
+    }
+    out.push(0xff);
+}
+
+fn int(x: &str) -> PlutusData {
+    PlutusData::Int(x.parse().unwrap())
+}
+
+/// The datum, as `Constr 0 [pub1, solution_hash, solver]`, the solution hash an option
+pub fn datum(pub_inputs: &PubInputs) -> PlutusData {
+    PlutusData::Constr(
+        0,
+        vec![
+            int(&pub_inputs.pub1),
+            PlutusData::option(pub_inputs.solution_hash.as_deref().map(int)),
+            PlutusData::List(pub_inputs.solver.iter().map(|x| int(x)).collect()),
+        ],
+    )
+}
+
+/// A bytestring of the compressed encoding
+fn compressed<T: CanonicalSerialize>(x: &T) -> PlutusData {
+    let mut bytes = vec![];
+    x.serialize_compressed(&mut bytes).unwrap();
+    PlutusData::Bytes(bytes)
+}
+
+/// The redeemer, as `Constr 0 [a, b, c]` with each point compressed as in `redeemer.json`
+pub fn redeemer<E: Pairing>(proof: &Proof<E>) -> PlutusData {
+    PlutusData::Constr(
+        0,
+        vec![
+            compressed(&proof.a),
+            compressed(&proof.b),
+            compressed(&proof.c),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use crate::plutus::{datum, PlutusData};
+    use crate::ss_serde::{unhex, PubInputs};
+
+    #[test]
+    fn test_plutus_data() {
+        let int = |x: u64| PlutusData::Int(BigUint::from(x));
+        assert_eq!(PlutusData::Constr(0, vec![]).to_cbor(), unhex("d87980"));
+        assert_eq!(
+            PlutusData::Constr(1, vec![int(1), int(500)]).to_cbor(),
+            unhex("d87a9f011901f4ff")
+        );
+        assert_eq!(PlutusData::Constr(7, vec![]).to_cbor(), unhex("d9050080"));
+        assert_eq!(
+            PlutusData::Constr(128, vec![]).to_cbor(),
+            unhex("d86682188080")
+        );
+
+        // Integers past 64 bits are bignums, and bytestrings past 64 bytes are chunked
+        let big = PlutusData::Int(BigUint::from(1u8) << 64);
+        assert_eq!(big.to_cbor(), unhex("c249010000000000000000"));
+        let long = PlutusData::Bytes(vec![0xab; 65]);
+        let mut cbor = unhex("5f5840");
+        cbor.extend([0xab; 64]);
+        cbor.extend(unhex("41abff"));
+        assert_eq!(long.to_cbor(), cbor);
+
+        let pub_inputs = PubInputs {
+            pub1: "18446744073709551616".to_string(),
+            solver: vec!["1".to_string(), "0".to_string()],
+            solution_hash: None,
+        };
+        assert_eq!(
+            datum(&pub_inputs).to_json(),
+            r#"{"constructor": 0, "fields": [{"int": 18446744073709551616}, {"constructor": 1, "fields": []}, {"list": [{"int": 1}, {"int": 0}]}]}"#
+        );
+        assert_eq!(
+            datum(&pub_inputs).to_cbor(),
+            unhex("d8799fc249010000000000000000d87a809f0100ffff")
+        );
+    }
+}
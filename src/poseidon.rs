@@ -15,10 +15,14 @@ use ark_r1cs_std::{
     R1CSVar,
 };
 use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::RngCore;
 
 pub fn mk_poseidon_config<F: PrimeField>(n: usize) -> PoseidonConfig<F> {
-    let mut test_rng = ark_std::test_rng();
+    rand_poseidon_config(n, &mut ark_std::test_rng())
+}
 
+/// Poseidon config of width `n`, with round constants and MDS matrix drawn from `rng`
+pub fn rand_poseidon_config<F: PrimeField, R: RngCore>(n: usize, rng: &mut R) -> PoseidonConfig<F> {
     let full_rounds = 8; //usize,
     let partial_rounds = 24; //usize,
     let alpha = 31; //u64,
@@ -35,21 +39,19 @@ pub fn mk_poseidon_config<F: PrimeField>(n: usize) -> PoseidonConfig<F> {
     let mut mds = vec![vec![]; n];
     // The following way of generating the MDS matrix is incorrect
     // and is only for test purposes.
-    for i in 0..n {
+    for row in mds.iter_mut() {
         for _ in 0..n {
-            mds[i].push(F::rand(&mut test_rng));
+            row.push(F::rand(rng));
         }
     }
 
-    let mut ark = vec![vec![]; full_rounds + 24];
-    for i in 0..full_rounds + partial_rounds {
+    let mut ark = vec![vec![]; full_rounds + partial_rounds];
+    for row in ark.iter_mut() {
         for _ in 0..n {
-            ark[i].push(F::rand(&mut test_rng));
+            row.push(F::rand(rng));
         }
     }
-    let params =
-        PoseidonConfig::<F>::new(full_rounds, partial_rounds, alpha, mds, ark, rate, capacity);
-    params
+    PoseidonConfig::<F>::new(full_rounds, partial_rounds, alpha, mds, ark, rate, capacity)
 }
 
 pub fn test_consistency<F: PrimeField + Absorb>(params: PoseidonConfig<F>, n: usize) {
@@ -105,3 +107,29 @@ pub fn hash_vec<F: PrimeField + Absorb>(params: PoseidonConfig<F>, v: Vec<F>) {
 
     assert_eq!(crh_a, crh_a_g.value().unwrap());
 }
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use proptest::prelude::*;
+
+    use crate::poseidon::{hash_vec, mk_poseidon_config, rand_poseidon_config, test_consistency};
+
+    #[test]
+    fn test_poseidon_consistency() {
+        for n in [2, 4, 9, 16, 25] {
+            test_consistency(mk_poseidon_config::<Fp>(n), n);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_hash_vec(seed: u64, n in 2_usize..10, v in prop::collection::vec(any::<u64>(), 0..20)) {
+            let params = rand_poseidon_config::<Fp, _>(n, &mut StdRng::seed_from_u64(seed));
+            hash_vec(params, v.into_iter().map(Fp::from).collect());
+        }
+    }
+}
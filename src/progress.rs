@@ -0,0 +1,231 @@
+//! Progress: prove a partly filled grid, 0 marking an empty cell, is consistent
+//! with the givens and breaks no row, column, box or parity rule,
+//! and has K cells filled beyond the givens, K public.
+//! Variant rules only bind the puzzle hash, as they cannot be judged on a partial grid.
+use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::prelude::{AllocVar, Boolean, EqGadget};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::cmp::CmpGadget;
+use crate::parity::{breaks_parity, Parity, ParityVar};
+use crate::rules::ExtraRule;
+use crate::{houses, Commitment, Grid, Puzzle, PuzzleVar, SolutionVar};
+
+#[derive(Clone, Debug)]
+pub struct Progress<const N: usize, F: PrimeField> {
+    pub poseidon_config: PoseidonConfig<F>,
+    pub hash: Option<F>,
+    pub puzzle: Option<Puzzle<N>>,
+    pub parity: Option<Parity<N>>,
+    /// Rules and commitment of the sudoku keys, so the puzzle hash is the same
+    pub rules: Vec<ExtraRule>,
+    pub commitment: Commitment,
+    pub salt: Option<F>,
+    /// The grid so far, 0 where empty
+    pub grid: Option<Grid<N>>,
+}
+
+impl<const N: usize, F: PrimeField> Progress<N, F> {
+    /// Cells filled beyond the givens
+    pub fn filled(&self) -> u64 {
+        let puzzle = self.puzzle.unwrap_or(Puzzle([[0; N]; N])).0;
+        let grid = self.grid.unwrap_or([[0; N]; N]);
+        puzzle
+            .iter()
+            .flatten()
+            .zip(grid.iter().flatten())
+            .filter(|(&p, &g)| p == 0 && g != 0)
+            .count() as u64
+    }
+
+    /// Public inputs in the order the circuit allocates them
+    pub fn public_inputs(&self) -> Vec<F> {
+        vec![self.hash.unwrap_or_default(), F::from(self.filled())]
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for Progress<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let filled = self.filled();
+        let puzzle_grid: Grid<N> = self.puzzle.unwrap_or(Puzzle([[0_u8; N]; N])).0;
+        let puzzle_var = PuzzleVar::<N, F>::new_witness(cs.clone(), || Ok(puzzle_grid)).unwrap();
+        let parity_grid: Grid<N> = self.parity.unwrap_or_default().0;
+        let parity_var = ParityVar::<N, F>::new_witness(cs.clone(), || Ok(parity_grid)).unwrap();
+        let grid: Grid<N> = self.grid.unwrap_or([[0_u8; N]; N]);
+        let grid_var = SolutionVar::<N, F>::new_witness(cs.clone(), || Ok(grid)).unwrap();
+
+        let empty = UInt8::constant(0);
+        let mut count = FpVar::zero();
+        for ((p_row, s_row), m_row) in puzzle_var.0.iter().zip(&grid_var.0).zip(&parity_var.0) {
+            for ((p, s), m) in p_row.iter().zip(s_row).zip(m_row) {
+                // In range, empty allowed
+                s.is_leq(&UInt8::constant(N as u8))?
+                    .enforce_equal(&Boolean::TRUE)?;
+                // Givens are kept
+                let is_given = p.is_neq(&empty)?;
+                is_given
+                    .and(&p.is_neq(s)?)?
+                    .enforce_equal(&Boolean::FALSE)?;
+                // Filled cells keep to their mark
+                let is_empty = s.is_eq(&empty)?;
+                breaks_parity(m, s)?
+                    .and(&is_empty.not())?
+                    .enforce_equal(&Boolean::FALSE)?;
+                count += FpVar::from(is_given.not().and(&is_empty.not())?);
+            }
+        }
+        // Filled cells of a row, column or box are distinct
+        for house in houses(&grid_var.0) {
+            for (j, cell) in house.iter().enumerate() {
+                for prior_cell in &house[0..j] {
+                    cell.is_eq(&empty)?
+                        .or(&cell.is_neq(prior_cell)?)?
+                        .enforce_equal(&Boolean::TRUE)?;
+                }
+            }
+        }
+
+        // Hash puzzle agrees with hash
+        let params_g =
+            CRHParametersVar::<F>::new_witness(cs.clone(), || Ok(&self.poseidon_config)).unwrap();
+        let salt_var = FpVar::new_witness(cs.clone(), || Ok(self.salt.unwrap_or_default()))?;
+        let hash_gadget = self.commitment.commit_var(
+            &params_g,
+            &puzzle_var.0,
+            &parity_var.0,
+            &self.rules,
+            &salt_var,
+        )?;
+        let hash_var = FpVar::new_input(cs.clone(), || {
+            self.hash.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        hash_gadget.enforce_equal(&hash_var)?;
+
+        // Count of filled cells agrees with K
+        let filled_var = FpVar::new_input(cs.clone(), || Ok(F::from(filled)))?;
+        count.enforce_equal(&filled_var)?;
+
+        Ok(())
+    }
+}
+
+/// The circuit without a witness, as setup synthesizes it
+pub fn blank_progress<const N: usize, F: PrimeField>(
+    poseidon_config: &PoseidonConfig<F>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> Progress<N, F> {
+    Progress {
+        poseidon_config: poseidon_config.clone(),
+        hash: None,
+        puzzle: None,
+        parity: None,
+        rules: rules.to_vec(),
+        commitment,
+        salt: None,
+        grid: None,
+    }
+}
+
+pub fn setup<const N: usize, E>(
+    poseidon_config: &PoseidonConfig<E::ScalarField>,
+    rules: &[ExtraRule],
+    commitment: Commitment,
+) -> (ProvingKey<E>, VerifyingKey<E>)
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    // Secrets from the OS, as for the main circuit
+    let mut rng = rand::rngs::OsRng;
+
+    Groth16::<E>::setup(
+        blank_progress::<N, E::ScalarField>(poseidon_config, rules, commitment),
+        &mut rng,
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use crate::parity::{Parity, ODD};
+    use crate::poseidon::mk_poseidon_config;
+    use crate::progress::Progress;
+    use crate::{commit_puzzle, Commitment, Grid, Puzzle};
+
+    fn check(grid: Grid<4>, filled: u64, expected: bool) {
+        let poseidon_config = mk_poseidon_config::<Fr>(4);
+        let puzzle = Puzzle([[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]]);
+        let mut parity = Parity::default();
+        parity.0[0][1] = ODD;
+        let progress = Progress {
+            hash: Some(commit_puzzle(
+                &poseidon_config,
+                &puzzle,
+                &parity,
+                &[],
+                Fr::from(0),
+            )),
+            poseidon_config,
+            puzzle: Some(puzzle),
+            parity: Some(parity),
+            rules: vec![],
+            commitment: Commitment::Flat,
+            salt: None,
+            grid: Some(grid),
+        };
+        assert_eq!(progress.filled(), filled);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        progress.generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_progress() {
+        // Just the givens, then some progress, then the whole solution
+        check(
+            [[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]],
+            0,
+            true,
+        );
+        check(
+            [[1, 0, 3, 4], [3, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]],
+            2,
+            true,
+        );
+        check(
+            [[1, 3, 2, 4], [2, 4, 1, 3], [3, 1, 4, 2], [4, 2, 3, 1]],
+            10,
+            true,
+        );
+        // A given is dropped
+        check(
+            [[0, 0, 3, 4], [3, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]],
+            2,
+            false,
+        );
+        // A clash in a column
+        check(
+            [[1, 0, 3, 4], [0, 4, 3, 0], [0, 0, 4, 0], [4, 0, 0, 1]],
+            2,
+            false,
+        );
+        // Even where marked odd
+        check(
+            [[1, 2, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]],
+            1,
+            false,
+        );
+    }
+}
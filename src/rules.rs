@@ -0,0 +1,702 @@
+use std::fs;
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    fields::fp::FpVar,
+    prelude::{Boolean, EqGadget, FieldVar},
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+use serde::{Deserialize, Serialize};
+
+use crate::cmp::CmpGadget;
+use crate::gadgets::{count_visible, sandwich_sum, to_fp};
+use crate::{Grid, SolutionVar};
+
+/// A (row, column) position in the grid
+pub type Cell = (usize, usize);
+
+/// A constraint on the solution beyond the base sudoku rules
+pub trait Rule {
+    /// Check the rule against a filled grid
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool;
+
+    /// Check the rule against a partly filled grid, where 0 is an empty cell.
+    /// False only if no way of filling the empty cells can satisfy it.
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool;
+
+    /// Enforce the rule on the solution in the circuit
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError>;
+
+    /// Canonical encoding absorbed into the puzzle hash
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Why the rule cannot apply to an N x N grid, if it cannot.
+    /// The other methods assume the rule is valid.
+    fn validate<const N: usize>(&self) -> Result<(), String>;
+}
+
+/// Digits strictly increase from the bulb along the path
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thermometer {
+    pub path: Vec<Cell>,
+}
+
+/// Digits along the path sum to the digit in the circle
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arrow {
+    pub circle: Cell,
+    pub path: Vec<Cell>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KropkiKind {
+    /// Digits are consecutive
+    White,
+    /// One digit is double the other
+    Black,
+}
+
+/// A dot between two cells
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Kropki {
+    pub kind: KropkiKind,
+    pub cells: [Cell; 2],
+}
+
+type LineCell = fn(usize, usize) -> Cell;
+
+/// Clues outside the grid, indexed by the row or column they look along.
+/// `None` where a row or column has no clue.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeClues {
+    #[serde(default)]
+    pub top: Vec<Option<u16>>,
+    #[serde(default)]
+    pub bottom: Vec<Option<u16>>,
+    #[serde(default)]
+    pub left: Vec<Option<u16>>,
+    #[serde(default)]
+    pub right: Vec<Option<u16>>,
+}
+
+/// Digits between the 1 and the N of a line sum to its clue
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sandwich {
+    #[serde(flatten)]
+    pub clues: EdgeClues,
+}
+
+/// Digits taller than all before them, seen from the clue, number the clue
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Skyscraper {
+    #[serde(flatten)]
+    pub clues: EdgeClues,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtraRule {
+    Thermometer(Thermometer),
+    Arrow(Arrow),
+    Kropki(Kropki),
+    Sandwich(Sandwich),
+    Skyscraper(Skyscraper),
+}
+
+impl Rule for Thermometer {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.path
+            .windows(2)
+            .all(|w| get(grid, w[0]) < get(grid, w[1]))
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        let filled = self
+            .path
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| get(grid, **c) != 0)
+            .map(|(k, c)| (k, get(grid, *c) as usize))
+            .collect::<Vec<_>>();
+        // Each digit leaves room for the cells before and after it
+        filled
+            .iter()
+            .all(|&(k, x)| x > k && x + self.path.len() - 1 - k <= N)
+            && filled
+                .windows(2)
+                .all(|w| w[1].1 >= w[0].1 + w[1].0 - w[0].0)
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        for w in self.path.windows(2) {
+            get_var(solution, w[0])
+                .is_lt(get_var(solution, w[1]))?
+                .enforce_equal(&Boolean::TRUE)?;
+        }
+        Ok(())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![1, len_to_byte(&self.path)];
+        bytes.extend(cells_to_bytes(&self.path));
+        bytes
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        validate_path::<N>(&self.path)
+    }
+}
+
+impl Rule for Arrow {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        let sum: u32 = self.path.iter().map(|c| get(grid, *c) as u32).sum();
+        sum == get(grid, self.circle) as u32
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        // Empty cells on the path add at least 1 each
+        let least: u32 = self.path.iter().map(|c| get(grid, *c).max(1) as u32).sum();
+        let empty = self.path.iter().any(|c| get(grid, *c) == 0);
+        match get(grid, self.circle) as u32 {
+            0 => least <= N as u32,
+            circle if empty => least <= circle,
+            circle => least == circle,
+        }
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        let mut sum = FpVar::zero();
+        for cell in &self.path {
+            sum += to_fp(get_var(solution, *cell))?;
+        }
+        sum.enforce_equal(&to_fp(get_var(solution, self.circle))?)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![2, len_to_byte(&self.path)];
+        bytes.extend(cells_to_bytes(&[self.circle]));
+        bytes.extend(cells_to_bytes(&self.path));
+        bytes
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        validate_path::<N>(&[self.circle])?;
+        validate_path::<N>(&self.path)
+    }
+}
+
+impl Rule for Kropki {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        let a = get(grid, self.cells[0]);
+        let b = get(grid, self.cells[1]);
+        match self.kind {
+            KropkiKind::White => a.abs_diff(b) == 1,
+            KropkiKind::Black => a as u16 == 2 * b as u16 || b as u16 == 2 * a as u16,
+        }
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.cells.iter().any(|c| get(grid, *c) == 0) || self.is_satisfied(grid)
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        let a = to_fp(get_var(solution, self.cells[0]))?;
+        let b = to_fp(get_var(solution, self.cells[1]))?;
+        // Each kind is a product of two alternatives, one of which must vanish
+        let (x, y) = match self.kind {
+            // (a - b - 1) * (a - b + 1) == 0
+            KropkiKind::White => {
+                let d = &a - &b;
+                (&d - FpVar::one(), d + FpVar::one())
+            }
+            // (a - 2b) * (b - 2a) == 0
+            KropkiKind::Black => (&a - b.double()?, b - a.double()?),
+        };
+        x.mul_equals(&y, &FpVar::zero())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let kind = match self.kind {
+            KropkiKind::White => 0,
+            KropkiKind::Black => 1,
+        };
+        let mut bytes = vec![3, kind];
+        bytes.extend(cells_to_bytes(&self.cells));
+        bytes
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        validate_path::<N>(&self.cells)
+    }
+}
+
+impl EdgeClues {
+    /// Each clue with the cells of its line, read from the clue inwards
+    fn lines<const N: usize>(&self) -> Vec<(u16, Vec<Cell>)> {
+        // Cell `x` along the line of clue `k`, for each edge
+        let edges: [(&Vec<Option<u16>>, LineCell); 4] = [
+            (&self.top, |k, x| (x, k)),
+            (&self.bottom, |k, x| (N - 1 - x, k)),
+            (&self.left, |k, x| (k, x)),
+            (&self.right, |k, x| (k, N - 1 - x)),
+        ];
+        let mut lines = vec![];
+        for (clues, cell) in edges {
+            for (k, clue) in clues.iter().enumerate() {
+                if let Some(clue) = clue {
+                    lines.push((*clue, (0..N).map(|x| cell(k, x)).collect()));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Whether the digits along each line hold for its clue,
+    /// skipping lines with an empty cell when `partial`
+    fn lines_hold<const N: usize>(
+        &self,
+        grid: &Grid<N>,
+        partial: bool,
+        holds: fn(&[u8], u16) -> bool,
+    ) -> bool {
+        self.lines::<N>().into_iter().all(|(clue, line)| {
+            let digits = line.iter().map(|c| get(grid, *c)).collect::<Vec<_>>();
+            (partial && digits.contains(&0)) || holds(&digits, clue)
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for clues in [&self.top, &self.bottom, &self.left, &self.right] {
+            bytes.push(len_to_byte(clues));
+            for clue in clues {
+                match clue {
+                    None => bytes.push(0),
+                    Some(clue) => {
+                        bytes.push(1);
+                        bytes.extend(clue.to_le_bytes());
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Each edge has at most a clue per line
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        for (edge, clues) in [
+            ("top", &self.top),
+            ("bottom", &self.bottom),
+            ("left", &self.left),
+            ("right", &self.right),
+        ] {
+            if clues.len() > N {
+                return Err(format!(
+                    "{} has {} clues, a grid of size {} takes at most {}",
+                    edge,
+                    clues.len(),
+                    N,
+                    N
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Digits between the 1 and the largest digit of a line sum to the clue
+fn sandwich_holds(digits: &[u8], clue: u16) -> bool {
+    let n = digits.len();
+    let crusts = (0..n)
+        .filter(|&x| digits[x] == 1 || digits[x] == n as u8)
+        .collect::<Vec<_>>();
+    crusts.len() == 2
+        && digits[crusts[0] + 1..crusts[1]]
+            .iter()
+            .map(|&d| d as u16)
+            .sum::<u16>()
+            == clue
+}
+
+/// Digits taller than all before them number the clue
+fn skyscraper_holds(digits: &[u8], clue: u16) -> bool {
+    let mut max = 0;
+    let mut count = 0;
+    for &x in digits {
+        if x > max {
+            max = x;
+            count += 1;
+        }
+    }
+    count == clue
+}
+
+impl Rule for Sandwich {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.clues.lines_hold(grid, false, sandwich_holds)
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.clues.lines_hold(grid, true, sandwich_holds)
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        for (clue, line) in self.clues.lines::<N>() {
+            let line = line
+                .iter()
+                .map(|c| get_var(solution, *c))
+                .collect::<Vec<_>>();
+            sandwich_sum(&line, 1, N as u8)?.enforce_equal(&FpVar::constant(F::from(clue)))?;
+        }
+        Ok(())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![4];
+        bytes.extend(self.clues.to_bytes());
+        bytes
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        self.clues.validate::<N>()
+    }
+}
+
+impl Rule for Skyscraper {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.clues.lines_hold(grid, false, skyscraper_holds)
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        self.clues.lines_hold(grid, true, skyscraper_holds)
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        for (clue, line) in self.clues.lines::<N>() {
+            let line = line
+                .iter()
+                .map(|c| get_var(solution, *c))
+                .collect::<Vec<_>>();
+            count_visible(&line)?.enforce_equal(&FpVar::constant(F::from(clue)))?;
+        }
+        Ok(())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![5];
+        bytes.extend(self.clues.to_bytes());
+        bytes
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        self.clues.validate::<N>()
+    }
+}
+
+impl Rule for ExtraRule {
+    fn is_satisfied<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        match self {
+            ExtraRule::Thermometer(rule) => rule.is_satisfied(grid),
+            ExtraRule::Arrow(rule) => rule.is_satisfied(grid),
+            ExtraRule::Kropki(rule) => rule.is_satisfied(grid),
+            ExtraRule::Sandwich(rule) => rule.is_satisfied(grid),
+            ExtraRule::Skyscraper(rule) => rule.is_satisfied(grid),
+        }
+    }
+
+    fn may_hold<const N: usize>(&self, grid: &Grid<N>) -> bool {
+        match self {
+            ExtraRule::Thermometer(rule) => rule.may_hold(grid),
+            ExtraRule::Arrow(rule) => rule.may_hold(grid),
+            ExtraRule::Kropki(rule) => rule.may_hold(grid),
+            ExtraRule::Sandwich(rule) => rule.may_hold(grid),
+            ExtraRule::Skyscraper(rule) => rule.may_hold(grid),
+        }
+    }
+
+    fn generate_constraints<const N: usize, F: PrimeField>(
+        &self,
+        solution: &SolutionVar<N, F>,
+    ) -> Result<(), SynthesisError> {
+        match self {
+            ExtraRule::Thermometer(rule) => rule.generate_constraints(solution),
+            ExtraRule::Arrow(rule) => rule.generate_constraints(solution),
+            ExtraRule::Kropki(rule) => rule.generate_constraints(solution),
+            ExtraRule::Sandwich(rule) => rule.generate_constraints(solution),
+            ExtraRule::Skyscraper(rule) => rule.generate_constraints(solution),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            ExtraRule::Thermometer(rule) => rule.to_bytes(),
+            ExtraRule::Arrow(rule) => rule.to_bytes(),
+            ExtraRule::Kropki(rule) => rule.to_bytes(),
+            ExtraRule::Sandwich(rule) => rule.to_bytes(),
+            ExtraRule::Skyscraper(rule) => rule.to_bytes(),
+        }
+    }
+
+    fn validate<const N: usize>(&self) -> Result<(), String> {
+        match self {
+            ExtraRule::Thermometer(rule) => rule.validate::<N>(),
+            ExtraRule::Arrow(rule) => rule.validate::<N>(),
+            ExtraRule::Kropki(rule) => rule.validate::<N>(),
+            ExtraRule::Sandwich(rule) => rule.validate::<N>(),
+            ExtraRule::Skyscraper(rule) => rule.validate::<N>(),
+        }
+    }
+}
+
+/// Read rules for an N x N grid from a json file. A missing file means no extra rules.
+/// Malformed json, or a rule that cannot apply to the grid, is an error.
+pub fn read_rules<const N: usize>(fp: &str) -> Result<Vec<ExtraRule>, String> {
+    let Ok(s) = fs::read_to_string(fp) else {
+        return Ok(vec![]);
+    };
+    let rules: Vec<ExtraRule> = serde_json::from_str(&s).map_err(|e| e.to_string())?;
+    for (i, rule) in rules.iter().enumerate() {
+        rule.validate::<N>()
+            .map_err(|e| format!("rule {}: {}", i, e))?;
+    }
+    Ok(rules)
+}
+
+pub fn write_rules(fp: &str, rules: &[ExtraRule]) -> Result<(), std::io::Error> {
+    fs::write(fp, serde_json::to_string_pretty(rules).unwrap())
+}
+
+fn get<const N: usize>(grid: &Grid<N>, (i, j): Cell) -> u8 {
+    grid[i][j]
+}
+
+fn get_var<const N: usize, F: PrimeField>(solution: &SolutionVar<N, F>, (i, j): Cell) -> &UInt8<F> {
+    &solution.0[i][j]
+}
+
+/// Cells are in the grid, and few enough to count in a byte
+fn validate_path<const N: usize>(cells: &[Cell]) -> Result<(), String> {
+    if cells.len() > u8::MAX as usize {
+        return Err(format!("{} cells, more than {}", cells.len(), u8::MAX));
+    }
+    match cells.iter().find(|&&(i, j)| i >= N || j >= N) {
+        Some(cell) => Err(format!("cell {:?} outside a grid of size {}", cell, N)),
+        None => Ok(()),
+    }
+}
+
+/// Length of a list in the encoding. Valid rules fit a byte.
+fn len_to_byte<T>(list: &[T]) -> u8 {
+    u8::try_from(list.len()).expect("rule too long to encode")
+}
+
+fn cells_to_bytes(cells: &[Cell]) -> Vec<u8> {
+    cells
+        .iter()
+        .flat_map(|&(i, j)| [i, j].map(|x| u8::try_from(x).expect("cell outside any grid")))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_r1cs_std::prelude::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::rules::{
+        read_rules, Arrow, EdgeClues, ExtraRule, Kropki, KropkiKind, Rule, Sandwich, Skyscraper,
+        Thermometer,
+    };
+    use crate::{Grid, SolutionVar};
+
+    const GRID: Grid<4> = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+    fn check(rule: &ExtraRule, expected: bool) {
+        assert_eq!(rule.is_satisfied(&GRID), expected, "{:?}", rule);
+        assert_eq!(rule.may_hold(&GRID), expected, "{:?}", rule);
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        let solution = SolutionVar::<4, Fp>::new_witness(cs.clone(), || Ok(GRID)).unwrap();
+        rule.generate_constraints(&solution).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), expected, "{:?}", rule);
+    }
+
+    #[test]
+    fn test_thermometer() {
+        let path = vec![(0, 0), (0, 1), (1, 1)];
+        check(&ExtraRule::Thermometer(Thermometer { path }), true);
+        let path = vec![(0, 0), (0, 1), (2, 1)];
+        check(&ExtraRule::Thermometer(Thermometer { path }), false);
+    }
+
+    #[test]
+    fn test_arrow() {
+        let rule = Arrow {
+            circle: (0, 2),
+            path: vec![(0, 0), (0, 1)],
+        };
+        check(&ExtraRule::Arrow(rule), true);
+        let rule = Arrow {
+            circle: (0, 3),
+            path: vec![(0, 0), (0, 1)],
+        };
+        check(&ExtraRule::Arrow(rule), false);
+    }
+
+    #[test]
+    fn test_kropki() {
+        let dot = |kind, cells| ExtraRule::Kropki(Kropki { kind, cells });
+        check(&dot(KropkiKind::White, [(0, 0), (0, 1)]), true);
+        check(&dot(KropkiKind::White, [(0, 0), (0, 2)]), false);
+        check(&dot(KropkiKind::Black, [(0, 1), (0, 3)]), true);
+        check(&dot(KropkiKind::Black, [(0, 2), (0, 3)]), false);
+    }
+
+    #[test]
+    fn test_sandwich() {
+        let clues = EdgeClues {
+            top: vec![Some(5)],
+            left: vec![Some(5), Some(0)],
+            ..Default::default()
+        };
+        check(&ExtraRule::Sandwich(Sandwich { clues }), true);
+        let clues = EdgeClues {
+            left: vec![None, Some(1)],
+            ..Default::default()
+        };
+        check(&ExtraRule::Sandwich(Sandwich { clues }), false);
+    }
+
+    #[test]
+    fn test_skyscraper() {
+        let clues = EdgeClues {
+            top: vec![Some(3)],
+            left: vec![Some(4)],
+            right: vec![Some(1)],
+            bottom: vec![None, None, None, Some(3)],
+        };
+        check(&ExtraRule::Skyscraper(Skyscraper { clues }), true);
+        let clues = EdgeClues {
+            top: vec![Some(2)],
+            ..Default::default()
+        };
+        check(&ExtraRule::Skyscraper(Skyscraper { clues }), false);
+    }
+
+    #[test]
+    fn test_validate() {
+        let thermometer = |path| ExtraRule::Thermometer(Thermometer { path });
+        assert!(thermometer(vec![(0, 0), (3, 3)]).validate::<4>().is_ok());
+        assert!(thermometer(vec![(0, 0), (4, 0)]).validate::<4>().is_err());
+        assert!(thermometer(vec![(0, 0); 256]).validate::<4>().is_err());
+        let arrow = Arrow {
+            circle: (0, 9),
+            path: vec![(0, 0)],
+        };
+        assert!(ExtraRule::Arrow(arrow).validate::<4>().is_err());
+        let dot = Kropki {
+            kind: KropkiKind::White,
+            cells: [(0, 0), (300, 0)],
+        };
+        assert!(ExtraRule::Kropki(dot).validate::<4>().is_err());
+        let clues = EdgeClues {
+            top: vec![None; 5],
+            ..Default::default()
+        };
+        assert_eq!(
+            ExtraRule::Sandwich(Sandwich { clues }).validate::<4>(),
+            Err("top has 5 clues, a grid of size 4 takes at most 4".to_string())
+        );
+
+        let dir = std::env::temp_dir().join(format!("rules-{}", std::process::id()));
+        let fp = dir.to_str().unwrap();
+        assert_eq!(read_rules::<4>(fp), Ok(vec![]));
+        std::fs::write(fp, "[{\"type\": \"thermometer\"}]").unwrap();
+        assert!(read_rules::<4>(fp).is_err());
+        std::fs::write(
+            fp,
+            "[{\"type\": \"thermometer\", \"path\": [[0, 0], [0, 4]]}]",
+        )
+        .unwrap();
+        assert!(read_rules::<4>(fp).unwrap_err().contains("(0, 4)"));
+        std::fs::remove_file(fp).unwrap();
+    }
+
+    #[test]
+    fn test_may_hold() {
+        let partial = |x, y| [[x, 0, y, 0], [0; 4], [0; 4], [0; 4]];
+        let thermometer = ExtraRule::Thermometer(Thermometer {
+            path: vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+        });
+        assert!(thermometer.may_hold(&partial(1, 0)));
+        // No room for three larger digits after a 2, or one between 1 and 2
+        assert!(!thermometer.may_hold(&partial(2, 0)));
+        assert!(!thermometer.may_hold(&partial(1, 2)));
+
+        let arrow = ExtraRule::Arrow(Arrow {
+            circle: (0, 2),
+            path: vec![(0, 0), (0, 1)],
+        });
+        assert!(arrow.may_hold(&partial(1, 3)));
+        assert!(!arrow.may_hold(&partial(1, 1)));
+        assert!(!arrow.may_hold(&partial(4, 0)));
+
+        let dot = ExtraRule::Kropki(Kropki {
+            kind: KropkiKind::White,
+            cells: [(0, 0), (0, 2)],
+        });
+        assert!(dot.may_hold(&partial(1, 0)));
+        assert!(!dot.may_hold(&partial(1, 3)));
+
+        // Lines are only checked once full
+        let clues = EdgeClues {
+            left: vec![Some(1)],
+            ..Default::default()
+        };
+        let sandwich = ExtraRule::Sandwich(Sandwich { clues });
+        assert!(sandwich.may_hold(&partial(1, 4)));
+        let mut full = partial(1, 4);
+        full[0] = [1, 2, 4, 3];
+        assert!(!sandwich.may_hold(&full));
+    }
+
+    #[test]
+    fn test_rules_round_trip() {
+        let rules = vec![
+            ExtraRule::Thermometer(Thermometer {
+                path: vec![(0, 0), (0, 1)],
+            }),
+            ExtraRule::Kropki(Kropki {
+                kind: KropkiKind::Black,
+                cells: [(1, 1), (1, 2)],
+            }),
+            ExtraRule::Skyscraper(Skyscraper {
+                clues: EdgeClues {
+                    left: vec![None, Some(2)],
+                    ..Default::default()
+                },
+            }),
+        ];
+        let s = serde_json::to_string(&rules).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<ExtraRule>>(&s).unwrap(), rules);
+    }
+}
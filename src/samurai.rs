@@ -0,0 +1,348 @@
+//! Samurai sudoku: five 9x9 grids laid out on a 21x21 board,
+//! the centre grid sharing each of its corner boxes with an outer grid.
+use std::fs;
+
+use ark_crypto_primitives::crh::poseidon::constraints::{CRHGadget, CRHParametersVar};
+use ark_crypto_primitives::crh::poseidon::CRH;
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::fp::{AllocatedFp, FpVar};
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::prelude::{AllocVar, EqGadget};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::pack::{cell_bits, pack, pack_var};
+use crate::{enforce_base_rules, Grid, GridVar, PuzSol, Puzzle, Solution, SOLVER_CHUNKS};
+
+/// Side of the board
+pub const SIZE: usize = 21;
+
+/// Side of each sub-grid
+const SUB: usize = 9;
+
+/// Top left corners of the sub-grids
+pub const OFFSETS: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+/// Whether a cell of the board lies in any sub-grid
+pub fn in_grid(i: usize, j: usize) -> bool {
+    OFFSETS
+        .iter()
+        .any(|&(oi, oj)| (oi..oi + SUB).contains(&i) && (oj..oj + SUB).contains(&j))
+}
+
+pub struct SamuraiVar<F: PrimeField>(pub GridVar<SIZE, F>);
+
+#[derive(Clone, Debug)]
+pub struct Samurai<F: PrimeField> {
+    pub poseidon_config: PoseidonConfig<F>,
+    pub hash: Option<F>,
+    pub puzzle: Option<Puzzle<SIZE>>,
+    pub solution: Option<Solution<SIZE>>,
+    /// Identity of the solver the proof is bound to, packed by `pack_solver`
+    pub solver: Option<[F; SOLVER_CHUNKS]>,
+}
+
+impl<F: PrimeField> Samurai<F> {
+    /// Public inputs in the order the circuit allocates them
+    pub fn public_inputs(&self) -> Vec<F> {
+        let mut inputs = vec![self.hash.unwrap_or_default()];
+        inputs.extend(self.solver.unwrap_or_default());
+        inputs
+    }
+}
+
+impl<F: PrimeField + Absorb> ConstraintSynthesizer<F> for Samurai<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let puzzle_grid: Grid<SIZE> = self.puzzle.unwrap_or(Puzzle([[0_u8; SIZE]; SIZE])).0;
+        let puzzle_var = SamuraiVar::<F>::new_witness(cs.clone(), || Ok(puzzle_grid)).unwrap();
+        let solution_grid: Grid<SIZE> = self.solution.unwrap_or(Solution([[0_u8; SIZE]; SIZE])).0;
+        let solution_var = SamuraiVar::<F>::new_witness(cs.clone(), || Ok(solution_grid)).unwrap();
+
+        // Each sub-grid is a sudoku. Shared cells are the same variables in both grids.
+        for (oi, oj) in OFFSETS {
+            enforce_base_rules::<SUB, F>(
+                &sub_grid(&puzzle_var.0, oi, oj),
+                &sub_grid(&solution_var.0, oi, oj),
+            )?;
+        }
+
+        // Hash puzzle agrees with hash
+        let params_g =
+            CRHParametersVar::<F>::new_witness(cs.clone(), || Ok(&self.poseidon_config)).unwrap();
+        let hash_gadget = hash_puzzle_var(&params_g, &puzzle_var.0)?;
+
+        let hash_var =
+            cs.new_input_variable(|| self.hash.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let hash_fp = FpVar::Var(AllocatedFp::new(self.hash, hash_var, cs.clone()));
+        hash_gadget.enforce_equal(&hash_fp)?;
+
+        // Bind the proof to the solver, as for sudokus
+        let solver = self.solver.unwrap_or_default();
+        for chunk in solver {
+            let chunk_var = FpVar::new_input(cs.clone(), || Ok(chunk))?;
+            let _ = chunk_var.square()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn sub_grid<F: PrimeField>(grid: &GridVar<SIZE, F>, oi: usize, oj: usize) -> GridVar<SUB, F> {
+    std::array::from_fn(|i| std::array::from_fn(|j| grid[oi + i][oj + j].clone()))
+}
+
+/// Cells of the board which lie in some sub-grid, row by row
+fn cells<T>(grid: &[[T; SIZE]; SIZE]) -> impl Iterator<Item = &T> {
+    grid.iter().enumerate().flat_map(|(i, row)| {
+        row.iter()
+            .enumerate()
+            .filter(move |(j, _)| in_grid(i, *j))
+            .map(|(_, x)| x)
+    })
+}
+
+/// The circuit without a witness, as setup synthesizes it
+pub fn blank_samurai<F: PrimeField>(poseidon_config: &PoseidonConfig<F>) -> Samurai<F> {
+    Samurai {
+        poseidon_config: poseidon_config.clone(),
+        hash: None,
+        puzzle: None,
+        solution: None,
+        solver: None,
+    }
+}
+
+pub fn setup<E>(
+    poseidon_config: &PoseidonConfig<E::ScalarField>,
+) -> (ProvingKey<E>, VerifyingKey<E>)
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    // The setup secrets are the toxic waste, so must come from the OS and not a seed
+    let mut rng = rand::rngs::OsRng;
+
+    Groth16::<E>::setup(blank_samurai(poseidon_config), &mut rng).unwrap()
+}
+
+pub fn mk_samurai<E>(
+    poseidon_config: &PoseidonConfig<E::ScalarField>,
+    example: &PuzSol<SIZE>,
+) -> Samurai<E::ScalarField>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    let hash = hash_puzzle(poseidon_config, &example.puzzle);
+    Samurai {
+        poseidon_config: poseidon_config.clone(),
+        hash: Some(hash),
+        puzzle: Some(example.puzzle),
+        solution: Some(example.solution),
+        solver: None,
+    }
+}
+
+/// Poseidon hash of the givens of every sub-grid, each shared cell taken once
+pub fn hash_puzzle<F: PrimeField + Absorb>(
+    poseidon_config: &PoseidonConfig<F>,
+    puzzle: &Puzzle<SIZE>,
+) -> F {
+    let x = pack::<F>(
+        &cells(&puzzle.0)
+            .map(|&x| (x, cell_bits(SUB)))
+            .collect::<Vec<_>>(),
+    );
+    CRH::<F>::evaluate(poseidon_config, x).unwrap()
+}
+
+/// Hash of the puzzle in the circuit, packed as by `hash_puzzle`
+pub fn hash_puzzle_var<F: PrimeField + Absorb>(
+    params: &CRHParametersVar<F>,
+    puzzle: &GridVar<SIZE, F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let x = pack_var(
+        &cells(puzzle)
+            .map(|x| (x, cell_bits(SUB)))
+            .collect::<Vec<_>>(),
+    )?;
+    CRHGadget::<F>::evaluate(params, &x)
+}
+
+/// Read a board as space separated rows, with `.` marking cells outside every sub-grid
+pub fn read_samurai(fp: &str) -> Grid<SIZE> {
+    let mut grid = [[0; SIZE]; SIZE];
+    let rows = fs::read_to_string(fp).unwrap();
+    let rows = rows
+        .lines()
+        .filter(|row| !row.trim().is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), SIZE, "wrong number of rows in {}", fp);
+    for (i, row) in rows.into_iter().enumerate() {
+        let row = row.split_whitespace().collect::<Vec<_>>();
+        assert_eq!(
+            row.len(),
+            SIZE,
+            "wrong number of cells in row {} of {}",
+            i,
+            fp
+        );
+        for (j, x) in row.into_iter().enumerate() {
+            match (x, in_grid(i, j)) {
+                (".", false) => {}
+                (x, true) => grid[i][j] = x.parse::<u8>().unwrap(),
+                (x, false) => panic!("expected . at ({}, {}) of {}, found {}", i, j, fp, x),
+            }
+        }
+    }
+    grid
+}
+
+pub fn write_samurai(fp: &str, grid: Grid<SIZE>) -> Result<(), std::io::Error> {
+    let s = grid
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, x)| match in_grid(i, j) {
+                    true => x.to_string(),
+                    false => ".".to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    fs::write(fp, format!("{}\n", s))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr as Fp;
+    use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+    use ark_r1cs_std::{prelude::AllocVar, R1CSVar};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use proptest::prelude::*;
+
+    use crate::poseidon::{mk_poseidon_config, rand_poseidon_config};
+    use crate::samurai::{hash_puzzle, hash_puzzle_var, in_grid, read_samurai, write_samurai};
+    use crate::samurai::{Samurai, SamuraiVar, SIZE};
+    use crate::{pack_solver, Grid, Puzzle, Solution, SOLVER_CHUNKS};
+
+    /// Every sub-grid sits at a multiple of 6, so one pattern over the board
+    /// gives a valid sudoku in each, up to relabelling digits.
+    fn solution() -> Grid<SIZE> {
+        std::array::from_fn(|i| {
+            std::array::from_fn(|j| match in_grid(i, j) {
+                true => ((i * 3 + i / 3 + j) % 9 + 1) as u8,
+                false => 0,
+            })
+        })
+    }
+
+    fn is_satisfied(puzzle: Grid<SIZE>, solution: Grid<SIZE>) -> bool {
+        let poseidon_config = mk_poseidon_config::<Fp>(9);
+        let hash = hash_puzzle(&poseidon_config, &Puzzle(puzzle));
+        is_satisfied_with_hash(puzzle, solution, hash)
+    }
+
+    fn is_satisfied_with_hash(puzzle: Grid<SIZE>, solution: Grid<SIZE>, hash: Fp) -> bool {
+        let poseidon_config = mk_poseidon_config::<Fp>(9);
+        let puzzle = Puzzle(puzzle);
+        let samurai = Samurai {
+            hash: Some(hash),
+            poseidon_config,
+            puzzle: Some(puzzle),
+            solution: Some(Solution(solution)),
+            solver: None,
+        };
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        samurai.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_samurai() {
+        let solution = solution();
+        let mut puzzle = solution;
+        for (i, row) in puzzle.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                if (i + j) % 3 != 0 {
+                    *cell = 0;
+                }
+            }
+        }
+        assert!(is_satisfied(puzzle, solution));
+
+        // Break the centre grid in a box it shares with the top left grid
+        let mut bad = solution;
+        bad[7][7] = bad[7][8];
+        assert!(!is_satisfied(puzzle, bad));
+
+        // The puzzle must match its hash
+        assert!(!is_satisfied_with_hash(puzzle, solution, Fp::from(0)));
+    }
+
+    #[test]
+    fn test_samurai_solver() {
+        let poseidon_config = mk_poseidon_config::<Fp>(9);
+        let puzzle = Puzzle(solution());
+        let samurai = Samurai {
+            hash: Some(hash_puzzle(&poseidon_config, &puzzle)),
+            poseidon_config,
+            puzzle: Some(puzzle),
+            solution: Some(Solution(solution())),
+            solver: Some(pack_solver(&[7; 20])),
+        };
+        let inputs = samurai.public_inputs();
+        let cs = ConstraintSystem::<Fp>::new_ref();
+        samurai.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        // The solver chunks follow the hash as public inputs
+        assert_eq!(inputs.len(), 1 + SOLVER_CHUNKS);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+    }
+
+    #[test]
+    fn test_samurai_round_trip() {
+        // Named by process, so concurrent test runs do not share the file
+        let fp = std::env::temp_dir().join(format!("samurai-{}.samurai", std::process::id()));
+        let fp = fp.to_str().unwrap();
+        write_samurai(fp, solution()).unwrap();
+        assert_eq!(read_samurai(fp), solution());
+        std::fs::remove_file(fp).unwrap();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_hash_puzzle(
+            seed: u64,
+            width in 2_usize..10,
+            cells in prop::collection::vec(0..=9_u8, SIZE * SIZE),
+        ) {
+            let poseidon_config = rand_poseidon_config::<Fp, _>(width, &mut StdRng::seed_from_u64(seed));
+            let puzzle: Grid<SIZE> = std::array::from_fn(|i| {
+                std::array::from_fn(|j| if in_grid(i, j) { cells[i * SIZE + j] } else { 0 })
+            });
+
+            let cs = ConstraintSystem::<Fp>::new_ref();
+            let params = CRHParametersVar::new_witness(cs.clone(), || Ok(&poseidon_config)).unwrap();
+            let puzzle_var = SamuraiVar::new_witness(cs.clone(), || Ok(puzzle)).unwrap();
+            let hash = hash_puzzle_var(&params, &puzzle_var.0).unwrap();
+            prop_assert_eq!(
+                hash.value().unwrap(),
+                hash_puzzle(&poseidon_config, &Puzzle(puzzle))
+            );
+        }
+    }
+}
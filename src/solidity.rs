@@ -0,0 +1,349 @@
+//! Solidity verifier for keys over BN254, using the EVM's ecAdd, ecMul and ecPairing precompiles,
+//! and the calldata to call it with.
+//! Points are uint256 coordinates, with G2 coordinates imaginary part first as the precompiles expect.
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+use crate::ss_serde::field_to_dec;
+
+/// Signature of `verifyProof`, for the number of public inputs
+fn signature(inputs: usize) -> String {
+    format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+        inputs
+    )
+}
+
+/// Coordinates of a G1 point, with the point at infinity as (0, 0)
+fn g1_coords(p: &G1Affine) -> [Fq; 2] {
+    p.xy().map_or([Fq::zero(); 2], |(x, y)| [*x, *y])
+}
+
+/// Coordinates of a G2 point, imaginary part first, with the point at infinity as zeros
+fn g2_coords(p: &G2Affine) -> [Fq; 4] {
+    p.xy()
+        .map_or([Fq::zero(); 4], |(x, y)| [x.c1, x.c0, y.c1, y.c0])
+}
+
+/// Big endian 32 byte word of a field element
+fn word<F: PrimeField>(x: &F) -> Vec<u8> {
+    x.into_bigint().to_bytes_be()
+}
+
+fn g1_constants(name: &str, p: &G1Affine) -> String {
+    let [x, y] = g1_coords(p);
+    format!(
+        "    uint256 constant {0}_X = {1};\n    uint256 constant {0}_Y = {2};\n",
+        name,
+        field_to_dec(&x),
+        field_to_dec(&y)
+    )
+}
+
+fn g2_constants(name: &str, p: &G2Affine) -> String {
+    ["X1", "X0", "Y1", "Y0"]
+        .iter()
+        .zip(g2_coords(p))
+        .map(|(suffix, x)| {
+            format!(
+                "    uint256 constant {}_{} = {};\n",
+                name,
+                suffix,
+                field_to_dec(&x)
+            )
+        })
+        .collect()
+}
+
+/// Source of a contract checking proofs under `vk` by `verifyProof(a, b, c, input)`,
+/// with the public inputs in the order the circuit allocates them
+pub fn verifier_contract(vk: &VerifyingKey<Bn254>) -> String {
+    let inputs = vk.gamma_abc_g1.len() - 1;
+    let mut constants = g1_constants("ALPHA", &vk.alpha_g1);
+    constants += &g2_constants("BETA", &vk.beta_g2);
+    constants += &g2_constants("GAMMA", &vk.gamma_g2);
+    constants += &g2_constants("DELTA", &vk.delta_g2);
+    for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+        constants += &g1_constants(&format!("IC{}", i), p);
+    }
+    let accumulate: String = (1..=inputs)
+        .map(|i| {
+            format!(
+                "        x = add(x, mul([IC{0}_X, IC{0}_Y], input[{1}]));\n",
+                i,
+                i - 1
+            )
+        })
+        .collect();
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by sudoku-snark export-solidity. Do not edit.
+pragma solidity ^0.8.0;
+
+/// Groth16 verifier of sudoku-snark proofs over BN254
+contract Verifier {{
+    // Scalar field modulus, bounding public inputs
+    uint256 constant R = {r};
+    // Base field modulus, for negating points
+    uint256 constant Q = {q};
+
+{constants}
+    /// Whether the proof holds for the public inputs
+    function verifyProof(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c,
+        uint256[{inputs}] calldata input
+    ) public view returns (bool) {{
+        for (uint256 i = 0; i < input.length; i++) {{
+            require(input[i] < R, "input not in the scalar field");
+        }}
+        // IC0 + sum of input[i] * IC(i + 1)
+        uint256[2] memory x = [IC0_X, IC0_Y];
+{accumulate}
+        // e(-A, B) e(alpha, beta) e(x, gamma) e(C, delta) == 1
+        uint256[24] memory p = [
+            a[0], (Q - a[1]) % Q, b[0][0], b[0][1], b[1][0], b[1][1],
+            ALPHA_X, ALPHA_Y, BETA_X1, BETA_X0, BETA_Y1, BETA_Y0,
+            x[0], x[1], GAMMA_X1, GAMMA_X0, GAMMA_Y1, GAMMA_Y0,
+            c[0], c[1], DELTA_X1, DELTA_X0, DELTA_Y1, DELTA_Y0
+        ];
+        uint256[1] memory out;
+        bool ok;
+        assembly {{
+            ok := staticcall(gas(), 0x08, p, 0x300, out, 0x20)
+        }}
+        return ok && out[0] == 1;
+    }}
+
+    function add(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory r) {{
+        uint256[4] memory input = [p[0], p[1], q[0], q[1]];
+        bool ok;
+        assembly {{
+            ok := staticcall(gas(), 0x06, input, 0x80, r, 0x40)
+        }}
+        require(ok, "ecAdd failed");
+    }}
+
+    function mul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {{
+        uint256[3] memory input = [p[0], p[1], s];
+        bool ok;
+        assembly {{
+            ok := staticcall(gas(), 0x07, input, 0x60, r, 0x40)
+        }}
+        require(ok, "ecMul failed");
+    }}
+}}
+"#,
+        r = Fr::MODULUS,
+        q = Fq::MODULUS,
+        constants = constants,
+        inputs = inputs,
+        accumulate = accumulate,
+    )
+}
+
+/// ABI encoded call of `verifyProof` with the proof and public inputs
+pub fn calldata(proof: &Proof<Bn254>, inputs: &[Fr]) -> Vec<u8> {
+    let mut data = Keccak256::digest(signature(inputs.len()))[..4].to_vec();
+    for x in g1_coords(&proof.a)
+        .iter()
+        .chain(&g2_coords(&proof.b))
+        .chain(&g1_coords(&proof.c))
+    {
+        data.extend(word(x));
+    }
+    for x in inputs {
+        data.extend(word(x));
+    }
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use ark_bn254::{Bn254, Fq, Fr};
+    use ark_ff::PrimeField;
+    use revm::primitives::{Address, ExecutionResult, Output, TxKind};
+    use revm::{Evm, InMemoryDB};
+
+    use crate::parity::Parity;
+    use crate::poseidon::mk_poseidon_config;
+    use crate::solidity::{calldata, verifier_contract, word};
+    use crate::ss_serde::{dec_to_field, unhex};
+    use crate::{mk_proof, mk_salt, mk_sudoku, pack_solver, setup, Commitment, PuzSol};
+    use crate::{Puzzle, Solution};
+
+    /// Output of the precompile at `address`, run by the EVM as the target of a transaction
+    fn precompile(address: u8, input: Vec<u8>) -> Vec<u8> {
+        let mut evm = Evm::builder()
+            .with_empty_db()
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Call(Address::with_last_byte(address));
+                tx.data = input.into();
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        match evm.transact().unwrap().result {
+            ExecutionResult::Success { output, .. } => output.into_data().to_vec(),
+            result => panic!("precompile {} failed: {:?}", address, result),
+        }
+    }
+
+    /// `verifyProof` of the contract source, step by step on the precompiles
+    fn run_contract(source: &str, calldata: &[u8]) -> bool {
+        let constant = |name: &str| {
+            let line = format!("uint256 constant {} = ", name);
+            let start = source.find(&line).unwrap() + line.len();
+            let end = start + source[start..].find(';').unwrap();
+            word(&dec_to_field::<Fq>(&source[start..end]))
+        };
+        let words = calldata[4..].chunks(32).collect::<Vec<_>>();
+        let (a, b, c, input) = (&words[..2], &words[2..6], &words[6..8], &words[8..]);
+
+        let mut x = [constant("IC0_X"), constant("IC0_Y")].concat();
+        for (i, s) in input.iter().enumerate() {
+            let ic = [
+                constant(&format!("IC{}_X", i + 1)),
+                constant(&format!("IC{}_Y", i + 1)),
+                s.to_vec(),
+            ];
+            x = precompile(6, [x, precompile(7, ic.concat())].concat());
+        }
+        let g2 = |name: &str| {
+            ["X1", "X0", "Y1", "Y0"]
+                .map(|suffix| constant(&format!("{}_{}", name, suffix)))
+                .concat()
+        };
+        let neg_a_y = word(&-Fq::from_be_bytes_mod_order(a[1]));
+        let pairing = [
+            a[0].to_vec(),
+            neg_a_y,
+            b.concat(),
+            constant("ALPHA_X"),
+            constant("ALPHA_Y"),
+            g2("BETA"),
+            x,
+            g2("GAMMA"),
+            c.concat(),
+            g2("DELTA"),
+        ];
+        precompile(8, pairing.concat())[31] == 1
+    }
+
+    /// Creation bytecode of the contract compiled by solc.
+    /// `SOLC` names the compiler binary, `solc` on the path by default.
+    fn compile(source: &str) -> Vec<u8> {
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".to_string());
+        let mut child = Command::new(&solc)
+            .args(["--combined-json", "bin", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap_or_else(|e| panic!("cannot run {}, set SOLC to a solc binary: {}", solc, e));
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(source.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "solc failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let contract = json["contracts"]
+            .as_object()
+            .unwrap()
+            .values()
+            .next()
+            .unwrap();
+        unhex(contract["bin"].as_str().unwrap())
+    }
+
+    /// Deploy the bytecode to a fresh chain, then call it with each calldata,
+    /// returning whether each call returned true
+    fn deploy_and_call(bytecode: Vec<u8>, calls: &[Vec<u8>]) -> Vec<bool> {
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Create;
+                tx.data = bytecode.into();
+                tx.gas_limit = 30_000_000;
+            })
+            .build();
+        let address = match evm.transact_commit().unwrap() {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => address,
+            result => panic!("deploying failed: {:?}", result),
+        };
+        calls
+            .iter()
+            .map(|data| {
+                evm.tx_mut().transact_to = TxKind::Call(address);
+                evm.tx_mut().data = data.clone().into();
+                match evm.transact_commit().unwrap() {
+                    ExecutionResult::Success { output, .. } => {
+                        let output = output.into_data();
+                        output.len() == 32 && output[31] == 1
+                    }
+                    result => panic!("verifyProof failed: {:?}", result),
+                }
+            })
+            .collect()
+    }
+
+    /// The contract of fresh keys, with the calldata of a good proof and of a bad one
+    fn contract_and_calls() -> (String, Vec<u8>, Vec<u8>) {
+        let poseidon_config = mk_poseidon_config::<Fr>(2);
+        let (pk, vk) = setup::<2, Bn254>(&poseidon_config, &[], Commitment::Flat);
+        let example = PuzSol {
+            puzzle: Puzzle([[1, 0], [0, 0]]),
+            solution: Solution([[1, 2], [2, 1]]),
+        };
+        let mut sudoku = mk_sudoku::<2, Bn254>(
+            &poseidon_config,
+            &example,
+            &Parity::default(),
+            &[],
+            Commitment::Flat,
+            mk_salt(),
+            mk_salt(),
+        );
+        sudoku.solver = Some(pack_solver(&[0xab; 40]));
+        let proof = mk_proof(&pk, &sudoku);
+        let mut inputs = sudoku.public_inputs();
+
+        let data = calldata(&proof, &inputs);
+        inputs[1] += Fr::from(1);
+        let wrong = calldata(&proof, &inputs);
+        (verifier_contract(&vk), data, wrong)
+    }
+
+    #[test]
+    fn test_verifier_contract() {
+        let (source, data, wrong) = contract_and_calls();
+        assert!(source.contains("uint256[4] calldata input"));
+        assert_eq!(data.len(), 4 + 32 * (8 + 4));
+        assert!(run_contract(&source, &data));
+        assert!(!run_contract(&source, &wrong));
+    }
+
+    #[test]
+    #[ignore = "needs solc, on the path or named by SOLC"]
+    fn test_deploy_verifier_contract() {
+        let (source, data, wrong) = contract_and_calls();
+        let bytecode = compile(&source);
+        assert_eq!(deploy_and_call(bytecode, &[data, wrong]), [true, false]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Native backtracking solver, so a setter can check their puzzle has exactly one solution.
+//! Cells are filled most constrained first, and a branch is dropped as soon as an extra rule
+//! cannot hold, so rules prune the search rather than only checking complete grids.
+use crate::parity::{keeps_mark, Parity};
+use crate::rules::{Cell, ExtraRule, Rule};
+use crate::{box_size, Grid, Puzzle};
+
+/// Solutions of the puzzle, stopping once `limit` are found
+pub fn solutions<const N: usize>(
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
+    limit: usize,
+) -> Vec<Grid<N>> {
+    let mut grid = puzzle.0;
+    let mut found = vec![];
+    // Givens must be in range, and agree with each other and the marks
+    for i in 0..N {
+        for j in 0..N {
+            let x = grid[i][j];
+            if x != 0 && (x as usize > N || !allowed(&grid, parity, (i, j), x)) {
+                return found;
+            }
+        }
+    }
+    search(&mut grid, parity, rules, limit, &mut found);
+    found
+}
+
+/// Number of solutions of the puzzle, counting no further than `limit`
+pub fn count_solutions<const N: usize>(
+    puzzle: &Puzzle<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
+    limit: usize,
+) -> usize {
+    solutions(puzzle, parity, rules, limit).len()
+}
+
+fn search<const N: usize>(
+    grid: &mut Grid<N>,
+    parity: &Parity<N>,
+    rules: &[ExtraRule],
+    limit: usize,
+    found: &mut Vec<Grid<N>>,
+) {
+    if !rules.iter().all(|rule| rule.may_hold(grid)) {
+        return;
+    }
+    // The empty cell with fewest candidates
+    let mut best: Option<(Cell, Vec<u8>)> = None;
+    for i in 0..N {
+        for j in 0..N {
+            if grid[i][j] != 0 {
+                continue;
+            }
+            let candidates = (1..=N as u8)
+                .filter(|&x| allowed(grid, parity, (i, j), x))
+                .collect::<Vec<_>>();
+            if best.as_ref().is_none_or(|b| candidates.len() < b.1.len()) {
+                best = Some(((i, j), candidates));
+            }
+        }
+    }
+    match best {
+        None => {
+            if rules.iter().all(|rule| rule.is_satisfied(grid)) {
+                found.push(*grid);
+            }
+        }
+        Some(((i, j), candidates)) => {
+            for x in candidates {
+                grid[i][j] = x;
+                search(grid, parity, rules, limit, found);
+                grid[i][j] = 0;
+                if found.len() >= limit {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Whether `x` can go at `cell` given its mark and the rest of its row, column and box
+fn allowed<const N: usize>(grid: &Grid<N>, parity: &Parity<N>, (i, j): Cell, x: u8) -> bool {
+    if !keeps_mark(parity.0[i][j], x) {
+        return false;
+    }
+    let clash = |(k, l): Cell| (k, l) != (i, j) && grid[k][l] == x;
+    if (0..N).any(|l| clash((i, l))) || (0..N).any(|k| clash((k, j))) {
+        return false;
+    }
+    match box_size(N) {
+        Some(b) => {
+            let (bi, bj) = (i - i % b, j - j % b);
+            !(bi..bi + b).any(|k| (bj..bj + b).any(|l| clash((k, l))))
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parity::{Parity, EVEN};
+    use crate::rules::{Arrow, ExtraRule, Thermometer};
+    use crate::solver::{count_solutions, solutions};
+    use crate::Puzzle;
+
+    #[test]
+    fn test_solutions() {
+        let puzzle = Puzzle([[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]]);
+        let parity = Parity::default();
+        assert_eq!(count_solutions(&puzzle, &parity, &[], 2), 2);
+
+        // One more given, or a mark, or a thermometer, makes it unique
+        let unique = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+        let mut more = puzzle;
+        more.0[0][1] = 2;
+        assert_eq!(solutions(&more, &parity, &[], 2), vec![unique]);
+        let mut marked = Parity::default();
+        marked.0[0][1] = EVEN;
+        assert_eq!(solutions(&puzzle, &marked, &[], 2), vec![unique]);
+        let thermometer = ExtraRule::Thermometer(Thermometer {
+            path: vec![(0, 1), (0, 2)],
+        });
+        assert_eq!(solutions(&puzzle, &parity, &[thermometer], 2), vec![unique]);
+
+        // Givens that clash have no solution
+        let mut clash = puzzle;
+        clash.0[0][2] = 1;
+        assert_eq!(count_solutions(&clash, &parity, &[], 2), 0);
+
+        // A blank 9x9 grid has many, counted no further than the limit
+        assert_eq!(
+            count_solutions(&Puzzle([[0; 9]; 9]), &Parity::default(), &[], 3),
+            3
+        );
+
+        // An impossible rule on a blank 9x9 grid is found out without filling it
+        let arrow = ExtraRule::Arrow(Arrow {
+            circle: (0, 0),
+            path: vec![(0, 1), (0, 2), (0, 3), (0, 4)],
+        });
+        assert_eq!(
+            count_solutions(&Puzzle([[0; 9]; 9]), &Parity::default(), &[arrow], 1),
+            0
+        );
+    }
+}
@@ -1,12 +1,112 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::AffineRepr;
 use ark_groth16::{Proof, VerifyingKey};
-use ark_serialize::{CanonicalSerialize, Compress};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::ValueEnum;
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
 use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
 
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+
+use crate::merkle::RowOpening;
+use crate::rules::ExtraRule;
+
+/// Byte encoding of the points in `params.json` and `redeemer.json`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// arkworks' compressed encoding, as the Plutus builtins take
+    #[default]
+    Compressed,
+    /// arkworks' uncompressed encoding
+    Uncompressed,
+    /// Big endian coordinates as the EVM precompiles take them, x then y, zeros at infinity.
+    /// Words are 32 bytes with G2 imaginary part first over BN254, as EIP-197,
+    /// and 64 bytes with G2 real part first over BLS12-381, as EIP-2537.
+    Evm,
+}
+
+/// Points in each `Encoding`
+pub trait EncodedPoint: Sized {
+    fn encode(&self, encoding: Encoding) -> Vec<u8>;
+    fn decode(bytes: &[u8], encoding: Encoding) -> Self;
+}
+
+/// Bytes of a word of the EVM encoding, for coordinates in `F`
+fn evm_word<F: Field>() -> usize {
+    if F::BasePrimeField::MODULUS_BIT_SIZE > 256 {
+        64
+    } else {
+        32
+    }
+}
+
+/// Base prime field elements of a coordinate, in the order of the EVM encoding
+fn to_evm_elements<F: Field>(x: &F) -> Vec<F::BasePrimeField> {
+    let mut elems = x.to_base_prime_field_elements().collect::<Vec<_>>();
+    if evm_word::<F>() == 32 {
+        elems.reverse();
+    }
+    elems
+}
+
+fn from_evm_elements<F: Field>(mut elems: Vec<F::BasePrimeField>) -> F {
+    if evm_word::<F>() == 32 {
+        elems.reverse();
+    }
+    F::from_base_prime_field_elems(&elems).unwrap()
+}
+
+impl<P: SWCurveConfig> EncodedPoint for Affine<P> {
+    fn encode(&self, encoding: Encoding) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match encoding {
+            Encoding::Compressed => self.serialize_compressed(&mut bytes).unwrap(),
+            Encoding::Uncompressed => self.serialize_uncompressed(&mut bytes).unwrap(),
+            Encoding::Evm => {
+                let word = evm_word::<P::BaseField>();
+                let (x, y) = self
+                    .xy()
+                    .map_or((P::BaseField::zero(), P::BaseField::zero()), |(x, y)| {
+                        (*x, *y)
+                    });
+                for e in to_evm_elements(&x).into_iter().chain(to_evm_elements(&y)) {
+                    let be = e.into_bigint().to_bytes_be();
+                    bytes.extend(vec![0; word - be.len()]);
+                    bytes.extend(be);
+                }
+            }
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8], encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Compressed => Affine::deserialize_compressed(bytes).unwrap(),
+            Encoding::Uncompressed => Affine::deserialize_uncompressed(bytes).unwrap(),
+            Encoding::Evm => {
+                let word = evm_word::<P::BaseField>();
+                let degree = P::BaseField::extension_degree() as usize;
+                assert_eq!(bytes.len(), 2 * degree * word, "wrong length of EVM point");
+                if bytes.iter().all(|b| *b == 0) {
+                    return Affine::identity();
+                }
+                let mut elems = bytes
+                    .chunks(word)
+                    .map(<P::BaseField as Field>::BasePrimeField::from_be_bytes_mod_order)
+                    .collect::<Vec<_>>();
+                let y = elems.split_off(degree);
+                Affine::new(from_evm_elements(elems), from_evm_elements(y))
+            }
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VkHexed {
@@ -14,49 +114,64 @@ pub struct VkHexed {
     beta2: String,
     gamma2: String,
     delta2: String,
-    abc11: String,
-    abc12: String,
+    /// `abc11`, `abc12`, ... one more than there are public inputs
+    #[serde(flatten)]
+    abc: BTreeMap<String, String>,
+}
+
+impl VkHexed {
+    /// The key, each point hex of its `encoding`
+    pub fn new<E>(vk: &VerifyingKey<E>, encoding: Encoding) -> VkHexed
+    where
+        E: Pairing,
+        E::G1Affine: EncodedPoint,
+        E::G2Affine: EncodedPoint,
+    {
+        let abc = vk
+            .gamma_abc_g1
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (format!("abc1{}", i + 1), hex_vec(&p.encode(encoding))))
+            .collect();
+        VkHexed {
+            alpha1: hex_vec(&vk.alpha_g1.encode(encoding)),
+            beta2: hex_vec(&vk.beta_g2.encode(encoding)),
+            gamma2: hex_vec(&vk.gamma_g2.encode(encoding)),
+            delta2: hex_vec(&vk.delta_g2.encode(encoding)),
+            abc,
+        }
+    }
+
+    /// The key back, its points read in `encoding`
+    pub fn vk<E>(&self, encoding: Encoding) -> VerifyingKey<E>
+    where
+        E: Pairing,
+        E::G1Affine: EncodedPoint,
+        E::G2Affine: EncodedPoint,
+    {
+        let g1 = |x: &str| E::G1Affine::decode(&unhex(x), encoding);
+        let g2 = |x: &str| E::G2Affine::decode(&unhex(x), encoding);
+        VerifyingKey {
+            alpha_g1: g1(&self.alpha1),
+            beta_g2: g2(&self.beta2),
+            gamma_g2: g2(&self.gamma2),
+            delta_g2: g2(&self.delta2),
+            // Keyed by index, as `abc110` sorts before `abc12`
+            gamma_abc_g1: (1..=self.abc.len())
+                .map(|i| g1(&self.abc[&format!("abc1{}", i)]))
+                .collect(),
+        }
+    }
 }
 
 impl<E> From<VerifyingKey<E>> for VkHexed
 where
     E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
 {
     fn from(vk: VerifyingKey<E>) -> VkHexed {
-        let mut alpha_1 = Vec::new();
-        let mut beta_2 = Vec::new();
-        let mut gamma_2 = Vec::new();
-        let mut delta_2 = Vec::new();
-        let mut gamma_abc_1_1 = Vec::new();
-        let mut gamma_abc_1_2 = Vec::new();
-
-        vk.alpha_g1
-            .serialize_with_mode(&mut alpha_1, Compress::Yes)
-            .unwrap();
-        vk.beta_g2
-            .serialize_with_mode(&mut beta_2, Compress::Yes)
-            .unwrap();
-        vk.gamma_g2
-            .serialize_with_mode(&mut gamma_2, Compress::Yes)
-            .unwrap();
-        vk.delta_g2
-            .serialize_with_mode(&mut delta_2, Compress::Yes)
-            .unwrap();
-        vk.gamma_abc_g1[0]
-            .serialize_with_mode(&mut gamma_abc_1_1, Compress::Yes)
-            .unwrap();
-        vk.gamma_abc_g1[1]
-            .serialize_with_mode(&mut gamma_abc_1_2, Compress::Yes)
-            .unwrap();
-
-        VkHexed {
-            alpha1: hex_vec(&alpha_1),
-            beta2: hex_vec(&beta_2),
-            gamma2: hex_vec(&gamma_2),
-            delta2: hex_vec(&delta_2),
-            abc11: hex_vec(&gamma_abc_1_1),
-            abc12: hex_vec(&gamma_abc_1_2),
-        }
+        VkHexed::new(&vk, Encoding::Compressed)
     }
 }
 
@@ -67,34 +182,98 @@ pub struct ProofHexed {
     c: String,
 }
 
+impl ProofHexed {
+    /// The proof, each point hex of its `encoding`
+    pub fn new<E>(proof: &Proof<E>, encoding: Encoding) -> ProofHexed
+    where
+        E: Pairing,
+        E::G1Affine: EncodedPoint,
+        E::G2Affine: EncodedPoint,
+    {
+        ProofHexed {
+            a: hex_vec(&proof.a.encode(encoding)),
+            b: hex_vec(&proof.b.encode(encoding)),
+            c: hex_vec(&proof.c.encode(encoding)),
+        }
+    }
+
+    /// The proof back, its points read in `encoding`
+    pub fn proof<E>(&self, encoding: Encoding) -> Proof<E>
+    where
+        E: Pairing,
+        E::G1Affine: EncodedPoint,
+        E::G2Affine: EncodedPoint,
+    {
+        Proof {
+            a: E::G1Affine::decode(&unhex(&self.a), encoding),
+            b: E::G2Affine::decode(&unhex(&self.b), encoding),
+            c: E::G1Affine::decode(&unhex(&self.c), encoding),
+        }
+    }
+}
+
 impl<E> From<Proof<E>> for ProofHexed
 where
     E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
 {
     fn from(proof: Proof<E>) -> ProofHexed {
-        let mut a = Vec::new();
-        let mut b = Vec::new();
-        let mut c = Vec::new();
-
-        proof.a.serialize_with_mode(&mut a, Compress::Yes).unwrap();
-        proof.b.serialize_with_mode(&mut b, Compress::Yes).unwrap();
-        proof.c.serialize_with_mode(&mut c, Compress::Yes).unwrap();
+        ProofHexed::new(&proof, Encoding::Compressed)
+    }
+}
 
-        ProofHexed {
-            a: hex_vec(&a),
-            b: hex_vec(&b),
-            c: hex_vec(&c),
-        }
+impl<E> From<ProofHexed> for Proof<E>
+where
+    E: Pairing,
+    E::G1Affine: EncodedPoint,
+    E::G2Affine: EncodedPoint,
+{
+    fn from(proof: ProofHexed) -> Proof<E> {
+        proof.proof(Encoding::Compressed)
     }
 }
 
-fn hex_vec(v: &Vec<u8>) -> String {
-    v.into_iter()
+pub fn hex_vec(v: &[u8]) -> String {
+    v.iter()
         .map(|x| format!("{:02x}", x))
         .collect::<Vec<String>>()
         .join("")
 }
 
+/// Decimal string of a field element. Unlike `Display`, this gives "0" for zero.
+pub fn field_to_dec<F: PrimeField>(x: &F) -> String {
+    let x: BigUint = (*x).into();
+    x.to_string()
+}
+
+/// Field element of a decimal string, as written by `field_to_dec`
+pub fn dec_to_field<F: PrimeField>(s: &str) -> F {
+    BigUint::from_str(s).unwrap().into()
+}
+
+/// Field element of a decimal string, refusing anything but the decimal `field_to_dec` writes,
+/// so a value at or above the modulus is an error rather than reduced
+pub fn canonical_dec_to_field<F: PrimeField>(s: &str) -> Result<F, String> {
+    let x = BigUint::from_str(s).map_err(|_| format!("{:?} is not a decimal", s))?;
+    if x.to_string() != s {
+        return Err(format!("{:?} is not a canonical decimal", s));
+    }
+    if x >= F::MODULUS.into() {
+        return Err(format!("{} is not below the field modulus", s));
+    }
+    Ok(x.into())
+}
+
+pub fn unhex(s: &str) -> Vec<u8> {
+    let s = s.trim_start_matches("0x");
+    assert!(s.len().is_multiple_of(2), "odd length hex string");
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PoseidonConfigDef {
     pub alpha: u64,
@@ -106,22 +285,22 @@ pub struct PoseidonConfigDef {
     pub rate: usize,
 }
 
-impl<F: PrimeField> Into<PoseidonConfig<F>> for PoseidonConfigDef {
-    fn into(self) -> PoseidonConfig<F> {
+impl<F: PrimeField> From<PoseidonConfigDef> for PoseidonConfig<F> {
+    fn from(def: PoseidonConfigDef) -> PoseidonConfig<F> {
         PoseidonConfig::<F>::new(
-            self.full_rounds,
-            self.partial_rounds,
-            self.alpha,
-            self.mds
+            def.full_rounds,
+            def.partial_rounds,
+            def.alpha,
+            def.mds
                 .into_iter()
                 .map(|row| row.into_iter().map(|x| x.into()).collect())
                 .collect(),
-            self.ark
+            def.ark
                 .into_iter()
                 .map(|row| row.into_iter().map(|x| x.into()).collect())
                 .collect(),
-            self.rate,
-            self.capacity,
+            def.rate,
+            def.capacity,
         )
     }
 }
@@ -151,4 +330,377 @@ impl<F: PrimeField> From<PoseidonConfig<F>> for PoseidonConfigDef {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PubInputs {
     pub pub1: String,
+    /// Packed solver identity, once a proof is bound to a solver
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub solver: Vec<String>,
+    /// Commitment to the solution, once a proof has been made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution_hash: Option<String>,
+}
+
+/// Public inputs of a hint, the cell and its value after the hashes of the sudoku
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HintInputs {
+    pub pub1: String,
+    pub solution_hash: String,
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+}
+
+/// Public inputs of a progress proof, the cells filled after the puzzle hash
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProgressInputs {
+    pub pub1: String,
+    pub filled: u64,
+}
+
+/// A setter's record of how many solutions a puzzle has, 2 meaning two or more.
+/// Anyone can check it by solving the puzzle again.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Certificate {
+    pub puzzle: Vec<Vec<u8>>,
+    pub parity: Vec<Vec<u8>>,
+    pub rules: Vec<ExtraRule>,
+    pub solutions: usize,
+}
+
+/// The setter's blinding for a salted puzzle commitment, kept private until opening
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Salt {
+    pub salt: String,
+}
+
+/// A revealed row of a Merkle committed puzzle
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RowOpeningDef {
+    pub index: usize,
+    pub cells: Vec<u8>,
+    pub marks: Vec<u8>,
+    pub salt: BigUint,
+    pub path: Vec<BigUint>,
+}
+
+impl<F: PrimeField> From<RowOpeningDef> for RowOpening<F> {
+    fn from(def: RowOpeningDef) -> RowOpening<F> {
+        RowOpening {
+            index: def.index,
+            cells: def.cells,
+            marks: def.marks,
+            salt: def.salt.into(),
+            path: def.path.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+impl<F: PrimeField> From<RowOpening<F>> for RowOpeningDef {
+    fn from(opening: RowOpening<F>) -> RowOpeningDef {
+        RowOpeningDef {
+            index: opening.index,
+            cells: opening.cells,
+            marks: opening.marks,
+            salt: opening.salt.into(),
+            path: opening.path.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+/// Points as snarkjs writes them, a projective triple of coordinates with z one, or zero at infinity.
+/// Each coordinate is the decimals of its base prime field elements, so one for G1
+/// and two, real part first, for G2.
+pub trait SnarkjsPoint: Sized {
+    fn to_snarkjs(&self) -> Vec<Vec<String>>;
+    fn from_snarkjs(coords: &[Vec<String>]) -> Self;
+}
+
+impl<P: SWCurveConfig> SnarkjsPoint for Affine<P> {
+    fn to_snarkjs(&self) -> Vec<Vec<String>> {
+        let dec = |x: P::BaseField| {
+            x.to_base_prime_field_elements()
+                .map(|x| field_to_dec(&x))
+                .collect()
+        };
+        let (zero, one) = (P::BaseField::zero(), P::BaseField::one());
+        match self.xy() {
+            Some((x, y)) => vec![dec(*x), dec(*y), dec(one)],
+            None => vec![dec(zero), dec(one), dec(zero)],
+        }
+    }
+
+    fn from_snarkjs(coords: &[Vec<String>]) -> Self {
+        let [x, y, z]: [P::BaseField; 3] = coords
+            .iter()
+            .map(|c| {
+                let elems = c
+                    .iter()
+                    .map(|x| canonical_dec_to_field(x).unwrap_or_else(|e| panic!("{}", e)))
+                    .collect::<Vec<_>>();
+                P::BaseField::from_base_prime_field_elems(&elems).unwrap()
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        if z.is_zero() {
+            return Affine::identity();
+        }
+        assert!(z.is_one(), "snarkjs point is not affine");
+        Affine::new(x, y)
+    }
+}
+
+/// A G1 point, whose coordinates snarkjs writes flat
+fn g1_to_snarkjs<A: SnarkjsPoint>(p: &A) -> Vec<String> {
+    p.to_snarkjs().concat()
+}
+
+fn g1_from_snarkjs<A: SnarkjsPoint>(coords: &[String]) -> A {
+    A::from_snarkjs(&coords.iter().map(|x| vec![x.clone()]).collect::<Vec<_>>())
+}
+
+/// Verifying key in snarkjs' Groth16 `verification_key.json` layout
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SnarkjsVk {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+impl SnarkjsVk {
+    /// The key, with the curve named as snarkjs does, `bn128` or `bls12381`
+    pub fn new<E>(vk: &VerifyingKey<E>, curve: &str) -> SnarkjsVk
+    where
+        E: Pairing,
+        E::G1Affine: SnarkjsPoint,
+        E::G2Affine: SnarkjsPoint,
+    {
+        SnarkjsVk {
+            protocol: "groth16".to_string(),
+            curve: curve.to_string(),
+            n_public: vk.gamma_abc_g1.len() - 1,
+            vk_alpha_1: g1_to_snarkjs(&vk.alpha_g1),
+            vk_beta_2: vk.beta_g2.to_snarkjs(),
+            vk_gamma_2: vk.gamma_g2.to_snarkjs(),
+            vk_delta_2: vk.delta_g2.to_snarkjs(),
+            ic: vk.gamma_abc_g1.iter().map(g1_to_snarkjs).collect(),
+        }
+    }
+}
+
+impl<E> From<SnarkjsVk> for VerifyingKey<E>
+where
+    E: Pairing,
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    fn from(vk: SnarkjsVk) -> VerifyingKey<E> {
+        assert_eq!(vk.protocol, "groth16", "not a Groth16 key");
+        assert_eq!(vk.ic.len(), vk.n_public + 1, "nPublic does not match IC");
+        VerifyingKey {
+            alpha_g1: g1_from_snarkjs(&vk.vk_alpha_1),
+            beta_g2: E::G2Affine::from_snarkjs(&vk.vk_beta_2),
+            gamma_g2: E::G2Affine::from_snarkjs(&vk.vk_gamma_2),
+            delta_g2: E::G2Affine::from_snarkjs(&vk.vk_delta_2),
+            gamma_abc_g1: vk.ic.iter().map(|p| g1_from_snarkjs(p)).collect(),
+        }
+    }
+}
+
+/// Proof in snarkjs' Groth16 `proof.json` layout.
+/// The public signals go alongside in `public.json`, as a list of decimals.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SnarkjsProof {
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+    pub protocol: String,
+    pub curve: String,
+}
+
+impl SnarkjsProof {
+    /// The proof, with the curve named as for `SnarkjsVk::new`
+    pub fn new<E>(proof: &Proof<E>, curve: &str) -> SnarkjsProof
+    where
+        E: Pairing,
+        E::G1Affine: SnarkjsPoint,
+        E::G2Affine: SnarkjsPoint,
+    {
+        SnarkjsProof {
+            pi_a: g1_to_snarkjs(&proof.a),
+            pi_b: proof.b.to_snarkjs(),
+            pi_c: g1_to_snarkjs(&proof.c),
+            protocol: "groth16".to_string(),
+            curve: curve.to_string(),
+        }
+    }
+}
+
+impl<E> From<SnarkjsProof> for Proof<E>
+where
+    E: Pairing,
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    fn from(proof: SnarkjsProof) -> Proof<E> {
+        assert_eq!(proof.protocol, "groth16", "not a Groth16 proof");
+        Proof {
+            a: g1_from_snarkjs(&proof.pi_a),
+            b: E::G2Affine::from_snarkjs(&proof.pi_b),
+            c: g1_from_snarkjs(&proof.pi_c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_ec::pairing::Pairing;
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_groth16::{Proof, VerifyingKey};
+    use ark_std::UniformRand;
+    use num_bigint::BigUint;
+
+    use crate::ss_serde::{canonical_dec_to_field, field_to_dec};
+    use crate::ss_serde::{EncodedPoint, Encoding, ProofHexed, VkHexed};
+    use crate::ss_serde::{SnarkjsPoint, SnarkjsProof, SnarkjsVk};
+
+    fn encoding_round_trip<E>(encoding: Encoding, g1_len: usize, g2_len: usize)
+    where
+        E: Pairing,
+        E::G1Affine: EncodedPoint,
+        E::G2Affine: EncodedPoint,
+    {
+        let rng = &mut ark_std::test_rng();
+        let vk = VerifyingKey::<E> {
+            alpha_g1: E::G1Affine::rand(rng),
+            beta_g2: E::G2Affine::rand(rng),
+            gamma_g2: E::G2Affine::rand(rng),
+            delta_g2: E::G2Affine::rand(rng),
+            // Past ten points, and the point at infinity
+            gamma_abc_g1: (0..11)
+                .map(|_| E::G1Affine::rand(rng))
+                .chain([E::G1Affine::zero()])
+                .collect(),
+        };
+        assert_eq!(vk.alpha_g1.encode(encoding).len(), g1_len);
+        assert_eq!(vk.beta_g2.encode(encoding).len(), g2_len);
+        assert_eq!(E::G1Affine::zero().encode(encoding).len(), g1_len);
+        let toj = serde_json::to_string(&VkHexed::new(&vk, encoding)).unwrap();
+        let back = serde_json::from_str::<VkHexed>(&toj).unwrap().vk(encoding);
+        assert_eq!(back, vk);
+
+        let proof = Proof::<E> {
+            a: E::G1Affine::rand(rng),
+            b: E::G2Affine::rand(rng),
+            c: E::G1Affine::rand(rng),
+        };
+        let toj = serde_json::to_string(&ProofHexed::new(&proof, encoding)).unwrap();
+        let back = serde_json::from_str::<ProofHexed>(&toj)
+            .unwrap()
+            .proof(encoding);
+        assert_eq!(back, proof);
+    }
+
+    #[test]
+    fn test_encodings() {
+        use ark_bls12_381::Bls12_381;
+        use ark_bn254::Bn254;
+        encoding_round_trip::<Bn254>(Encoding::Compressed, 32, 64);
+        encoding_round_trip::<Bn254>(Encoding::Uncompressed, 64, 128);
+        encoding_round_trip::<Bn254>(Encoding::Evm, 64, 128);
+        encoding_round_trip::<Bls12_381>(Encoding::Compressed, 48, 96);
+        encoding_round_trip::<Bls12_381>(Encoding::Uncompressed, 96, 192);
+        encoding_round_trip::<Bls12_381>(Encoding::Evm, 128, 256);
+
+        // The generators, big endian with G2 imaginary part first over BN254 and real part first over BLS12-381
+        let g1 = ark_bn254::G1Affine::generator().encode(Encoding::Evm);
+        assert_eq!(g1[31], 1);
+        assert_eq!(g1[63], 2);
+        let g2 = ark_bn254::G2Affine::generator().encode(Encoding::Evm);
+        assert_eq!(
+            &g2[32..64],
+            &ark_bn254::G2Affine::generator()
+                .x
+                .c0
+                .into_bigint()
+                .to_bytes_be()[..]
+        );
+        let g2 = ark_bls12_381::G2Affine::generator().encode(Encoding::Evm);
+        assert_eq!(&g2[..16], &[0; 16]);
+        assert_eq!(
+            &g2[16..64],
+            &ark_bls12_381::G2Affine::generator()
+                .x
+                .c0
+                .into_bigint()
+                .to_bytes_be()[..]
+        );
+    }
+
+    fn round_trip<E>(curve: &str)
+    where
+        E: Pairing,
+        E::G1Affine: SnarkjsPoint,
+        E::G2Affine: SnarkjsPoint,
+    {
+        let rng = &mut ark_std::test_rng();
+        let vk = VerifyingKey::<E> {
+            alpha_g1: E::G1Affine::rand(rng),
+            beta_g2: E::G2Affine::rand(rng),
+            gamma_g2: E::G2Affine::rand(rng),
+            delta_g2: E::G2Affine::rand(rng),
+            // The point at infinity too
+            gamma_abc_g1: vec![E::G1Affine::rand(rng), E::G1Affine::zero()],
+        };
+        let toj = serde_json::to_string(&SnarkjsVk::new(&vk, curve)).unwrap();
+        let back: VerifyingKey<E> = serde_json::from_str::<SnarkjsVk>(&toj).unwrap().into();
+        assert_eq!(back, vk);
+
+        let proof = Proof::<E> {
+            a: E::G1Affine::rand(rng),
+            b: E::G2Affine::rand(rng),
+            c: E::G1Affine::rand(rng),
+        };
+        let toj = serde_json::to_string(&SnarkjsProof::new(&proof, curve)).unwrap();
+        let back: Proof<E> = serde_json::from_str::<SnarkjsProof>(&toj).unwrap().into();
+        assert_eq!(back, proof);
+    }
+
+    #[test]
+    fn test_snarkjs() {
+        round_trip::<ark_bn254::Bn254>("bn128");
+        round_trip::<ark_bls12_381::Bls12_381>("bls12381");
+
+        // The layout snarkjs writes, z last and G2 coordinates in pairs
+        let vk = SnarkjsVk::new(
+            &VerifyingKey::<ark_bn254::Bn254> {
+                gamma_abc_g1: vec![ark_bn254::G1Affine::generator()],
+                ..Default::default()
+            },
+            "bn128",
+        );
+        let toj = serde_json::to_value(&vk).unwrap();
+        assert_eq!(toj["nPublic"], 0);
+        assert_eq!(toj["IC"][0], serde_json::json!(["1", "2", "1"]));
+        assert_eq!(toj["vk_alpha_1"], serde_json::json!(["0", "1", "0"]));
+        assert_eq!(
+            toj["vk_beta_2"],
+            serde_json::json!([["0", "0"], ["1", "0"], ["0", "0"]])
+        );
+
+        // Signals read back must be canonical, not reduced into the field
+        type Fr = ark_bn254::Fr;
+        let x = -Fr::from(1);
+        assert_eq!(canonical_dec_to_field::<Fr>(&field_to_dec(&x)), Ok(x));
+        let modulus = BigUint::from(Fr::MODULUS).to_string();
+        for s in [modulus.as_str(), "01", "+1", "", "0x1"] {
+            assert!(canonical_dec_to_field::<Fr>(s).is_err(), "{:?}", s);
+        }
+    }
 }